@@ -0,0 +1,170 @@
+// Compares the streaming xlsx reader with umya_spreadsheet on the DMFA fixture and on a synthetic DMFA
+// extract. The number of synthetic lines is read from LVGL_BENCH_ROWS (default 100000).
+//
+//     cargo bench --bench xlsx
+//     LVGL_BENCH_ROWS=1000000 cargo bench --bench xlsx
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use lvgl::{DmfaReader, XlsxReader};
+use umya_spreadsheet::reader::xlsx;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+/// The columns read by `DmfaReader::data`.
+const KOLOMMEN: [&str; 9] = ["Kwart", "WGC", "WNK", "INSZ", "T_nr", "T_begin", "T_eind", "LC", "LC_bedr"];
+
+/// Columns of the DMFA export that are not read, to keep the synthetic rows as wide as the real ones.
+const OPVULLING: usize = 40;
+
+/// Reads the DMFA columns of every line the way the readers did before streaming: load the whole
+/// workbook, then one `get_value` per field per row.
+fn umya(path: &Path) -> usize {
+    let book = xlsx::read(path).unwrap();
+    let sheet = book.get_sheet(&0).unwrap();
+    let columns: Vec<u32> = KOLOMMEN.iter()
+        .map(|name| {
+            sheet.get_collection_by_row_to_hashmap(&1)
+                .into_iter()
+                .find(|(_, cell)| cell.get_value().trim() == *name)
+                .map(|(column, _)| column)
+                .unwrap()
+        })
+        .collect();
+    (3..=sheet.get_highest_row())
+        .map(|row| columns.iter().map(|column| sheet.get_value((*column, row)).len()).sum::<usize>())
+        .filter(|len| *len > 0)
+        .count()
+}
+
+/// Reads the same columns with the streaming reader.
+fn streaming(path: &Path) -> usize {
+    let mut workbook = XlsxReader::open(path).unwrap();
+    let mut rows = workbook.sheet(0).unwrap();
+    let header = rows.next().unwrap().unwrap();
+    let columns: Vec<u32> = KOLOMMEN.iter()
+        .map(|name| header.cells().find(|(_, value)| value.trim() == *name).map(|(column, _)| column).unwrap())
+        .collect();
+    rows.map(Result::unwrap)
+        .filter(|row| row.number >= 3)
+        .map(|row| columns.iter().map(|column| row.get(*column).len()).sum::<usize>())
+        .filter(|len| *len > 0)
+        .count()
+}
+
+/// Reads the typed DMFA lines.
+fn dmfa(path: &Path) -> usize {
+    let data = DmfaReader::new(path.to_str().unwrap()).unwrap().data().unwrap();
+    data.values().flat_map(|kwartalen| kwartalen.values()).map(Vec::len).sum()
+}
+
+/// Runs `f` `runs` times and returns the fastest run with its result.
+fn time(runs: usize, f: impl Fn() -> usize) -> (Duration, usize) {
+    (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            let result = f();
+            (start.elapsed(), result)
+        })
+        .min()
+        .unwrap()
+}
+
+fn inline(kolom: &str, rij: usize, waarde: &str) -> String {
+    format!(r#"<c r="{}{}" t="inlineStr"><is><t>{}</t></is></c>"#, kolom, rij, waarde)
+}
+
+fn getal(kolom: &str, rij: usize, waarde: impl std::fmt::Display) -> String {
+    format!(r#"<c r="{}{}"><v>{}</v></c>"#, kolom, rij, waarde)
+}
+
+fn kolom(index: usize) -> String {
+    let mut index = index + 1;
+    let mut letters = Vec::new();
+    while index > 0 {
+        letters.push(b'A' + ((index - 1) % 26) as u8);
+        index = (index - 1) / 26;
+    }
+    letters.reverse();
+    String::from_utf8(letters).unwrap()
+}
+
+/// Writes a synthetic single-sheet DMFA extract with `rows` wage lines.
+fn synthetic(path: &Path, rows: usize) {
+    let mut zip = ZipWriter::new(File::create(path).unwrap());
+    let options = SimpleFileOptions::default();
+    let parts = [
+        ("[Content_Types].xml", r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/><Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/></Types>"#),
+        ("_rels/.rels", r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#),
+        ("xl/workbook.xml", r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="DECLARATIONS_20231_20234_020752" sheetId="1" r:id="rId1"/></sheets></workbook>"#),
+        ("xl/_rels/workbook.xml.rels", r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/></Relationships>"#),
+        ("xl/styles.xml", r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><fonts count="1"><font><sz val="11"/><name val="Calibri"/></font></fonts><fills count="1"><fill><patternFill patternType="none"/></fill></fills><borders count="1"><border/></borders><cellStyleXfs count="1"><xf/></cellStyleXfs><cellXfs count="1"><xf/></cellXfs></styleSheet>"#),
+    ];
+    for (name, content) in parts {
+        zip.start_file(name, options).unwrap();
+        zip.write_all(content.as_bytes()).unwrap();
+    }
+
+    zip.start_file("xl/worksheets/sheet1.xml", options).unwrap();
+    zip.write_all(br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>"#).unwrap();
+    let header: Vec<&str> = ["KBO"].into_iter().chain(KOLOMMEN).collect();
+    let fr: Vec<&str> = ["BCE"].into_iter().chain(KOLOMMEN).collect();
+    for (rij, namen) in [(1, &header), (2, &fr)] {
+        let cells: String = namen.iter().enumerate().map(|(index, naam)| inline(&kolom(index), rij, naam)).collect();
+        write!(zip, r#"<row r="{}">{}</row>"#, rij, cells).unwrap();
+    }
+    for line in 0..rows {
+        let rij = line + 3;
+        // Valid INSZ numbers of persons born on 20/06/1950
+        let basis = 500_620_000 + (line % 997) as u64 + 1;
+        let insz = format!("{}{:02}", basis, 97 - basis % 97);
+        let mut cells = inline("A", rij, "0207527540");
+        cells += &getal("B", rij, 20231 + line % 4);
+        cells += &getal("C", rij, 751);
+        cells += &getal("D", rij, 15);
+        cells += &inline("E", rij, &insz);
+        cells += &getal("F", rij, 1);
+        cells += &getal("G", rij, 44927);
+        cells += &getal("H", rij, 45016);
+        cells += &getal("I", rij, 1 + line % 20);
+        cells += &getal("J", rij, format!("{}.{:02}", 1000 + line % 5000, line % 100));
+        for index in 0..OPVULLING {
+            cells += &getal(&kolom(KOLOMMEN.len() + 1 + index), rij, index);
+        }
+        write!(zip, r#"<row r="{}">{}</row>"#, rij, cells).unwrap();
+    }
+    zip.write_all(b"</sheetData></worksheet>").unwrap();
+    zip.finish().unwrap();
+}
+
+/// Times the streaming readers first: umya_spreadsheet may run out of memory on the largest extracts.
+fn report(name: &str, runs: usize, path: &Path) {
+    let size = std::fs::metadata(path).unwrap().len();
+    println!("{} ({} KB)", name, size / 1024);
+    let (streaming_tijd, streaming_rijen) = time(runs, || streaming(path));
+    println!("  XlsxReader        {:>10.1?}  {} rows", streaming_tijd, streaming_rijen);
+    let (dmfa_tijd, dmfa_rijen) = time(runs, || dmfa(path));
+    println!("  DmfaReader::data  {:>10.1?}  {} lines", dmfa_tijd, dmfa_rijen);
+    let (umya_tijd, umya_rijen) = time(runs, || umya(path));
+    assert_eq!(umya_rijen, streaming_rijen);
+    println!("  umya_spreadsheet  {:>10.1?}  {} rows  ({:.1}x slower)", umya_tijd, umya_rijen,
+        umya_tijd.as_secs_f64() / streaming_tijd.as_secs_f64());
+}
+
+fn main() {
+    report("tests/fixtures/207527540-dmfa.xlsx", 5, Path::new("tests/fixtures/207527540-dmfa.xlsx"));
+
+    let rows = std::env::var("LVGL_BENCH_ROWS").ok().and_then(|rows| rows.parse().ok()).unwrap_or(100_000);
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("synthetic-dmfa.xlsx");
+    synthetic(&path, rows);
+    report(&format!("synthetic DMFA, {} lines", rows), 1, &path);
+}
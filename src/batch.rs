@@ -0,0 +1,364 @@
+// Batch : the reconciliation of a whole folder of employers. Every employer has a DMFA export and a CIPAL or
+// BOSA loonbestand, named after its KBO number : `<kbo>-dmfa.xlsx`, `<kbo>-cipal.xlsx` or `<kbo>-bosa.xlsx`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use thiserror::Error;
+use crate::codes::Codes;
+use crate::compare::{compare_dmfa_bosa, compare_dmfa_cipal, CompareError, Verschil};
+use crate::export::{Table, Value, ValueType};
+use crate::mapping::{Mapping, NietGemapt};
+use crate::privacy::Privacy;
+use crate::report::{ReportError, VerschilReport};
+use crate::tolerantie::{Klasse, Tolerantie};
+use crate::types::{Kbo, KboError, Money};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum BatchError {
+    #[error("Directory {0} not found.")]
+    DirectoryNotFound(String),
+    #[error("Could not read directory {0}.")]
+    ReadError(String),
+    #[error("Could not create directory {0}.")]
+    WriteError(String),
+    #[error("DMFA document not found.")]
+    DmfaNotFound,
+    #[error("CIPAL or BOSA document not found.")]
+    LoonbestandNotFound,
+    #[error("Both a CIPAL and a BOSA document found.")]
+    MultipleLoonbestanden,
+    #[error("{0}")]
+    Compare(#[from] CompareError),
+    #[error("{0}")]
+    Report(#[from] ReportError),
+    #[error("Panic: {0}")]
+    Panic(String),
+}
+
+/// The documents of one employer found in the folder.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Employer {
+    pub kbo: String,
+    pub dmfa: Option<PathBuf>,
+    pub cipal: Option<PathBuf>,
+    pub bosa: Option<PathBuf>,
+}
+
+/// The documents found in a folder.
+#[derive(Debug, Default, PartialEq)]
+pub struct Scan {
+    /// The employers, sorted by KBO number.
+    pub employers: Vec<Employer>,
+    /// The documents whose prefix is not a valid KBO number, they are not paired.
+    pub invalid: Vec<(PathBuf, KboError)>,
+}
+
+/// The outcome of the reconciliation of one employer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+    /// The loonbestand the DMFA was compared with, "CIPAL" or "BOSA".
+    pub source: &'static str,
+    pub verschillen: Vec<Verschil>,
+    pub niet_gemapt: Vec<NietGemapt>,
+    /// The written verschil document.
+    pub report: PathBuf,
+}
+
+/// One employer with its outcome, or the reason it could not be reconciled.
+#[derive(Debug, PartialEq)]
+pub struct BatchResult {
+    pub employer: Employer,
+    pub outcome: Result<Outcome, BatchError>,
+}
+
+/// Finds the employers in a folder, sorted by KBO number.
+///
+/// Only the `<kbo>-dmfa.xlsx`, `<kbo>-cipal.xlsx` and `<kbo>-bosa.xlsx` files are picked up (case
+/// insensitive), other files such as the `<kbo>-verschil.xlsx` documents are ignored. The documents are paired
+/// on their KBO number, so `207527540-dmfa.xlsx` and `0207.527.540-cipal.xlsx` belong to the same employer,
+/// whose `kbo` is the 10 digits of the number. An employer is returned as soon as one of its documents is
+/// found, `Batch::run` reports the missing ones.
+///
+/// # Errors
+///
+/// Returns `BatchError::DirectoryNotFound` if `directory` is not a directory, and `BatchError::ReadError`
+/// if it can not be read.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+/// use lvgl::batch::scan;
+///
+/// let scan = scan(Path::new("tests/fixtures")).unwrap();
+/// assert_eq!(scan.employers[0].kbo, "0207527540");
+/// assert!(scan.employers[0].cipal.is_some());
+/// assert!(scan.invalid.is_empty());
+/// ```
+pub fn scan(directory: &Path) -> Result<Scan, BatchError> {
+    if !directory.is_dir() {
+        return Err(BatchError::DirectoryNotFound(directory.display().to_string()));
+    }
+    let read_error = || BatchError::ReadError(directory.display().to_string());
+
+    let mut employers: BTreeMap<String, Employer> = BTreeMap::new();
+    let mut invalid = Vec::new();
+    for entry in fs::read_dir(directory).map_err(|_| read_error())? {
+        let path = entry.map_err(|_| read_error())?.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()).map(str::to_lowercase) else {
+            continue;
+        };
+        let Some((prefix, source)) = name.strip_suffix(".xlsx").and_then(|stem| stem.rsplit_once('-')) else {
+            continue;
+        };
+        if !["dmfa", "cipal", "bosa"].contains(&source) || !path.is_file() {
+            continue;
+        }
+        let kbo = match Kbo::new(prefix) {
+            Ok(kbo) => kbo.as_str().to_string(),
+            Err(e) => {
+                invalid.push((path, e));
+                continue;
+            }
+        };
+
+        let employer = employers.entry(kbo.clone()).or_insert_with(|| Employer { kbo, ..Employer::default() });
+        match source {
+            "dmfa" => employer.dmfa = Some(path),
+            "cipal" => employer.cipal = Some(path),
+            _ => employer.bosa = Some(path),
+        }
+    }
+    invalid.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    Ok(Scan { employers: employers.into_values().collect(), invalid })
+}
+
+/// The settings of a batch run, shared by all employers.
+#[derive(Debug, Clone)]
+pub struct Batch {
+    /// The folder the verschil documents are written to, as `<kbo>-verschil.xlsx`.
+    pub output: PathBuf,
+    pub mapping: Mapping,
+    pub tolerantie: Tolerantie,
+    pub privacy: Privacy,
+    /// The catalogue explaining the codes in the verschil documents.
+    pub codes: Codes,
+    /// The number of employers reconciled at the same time.
+    pub jobs: usize,
+}
+
+impl Batch {
+    /// Creates a new `Batch` writing to `output`, with the standard mapping, the default tolerance, masked INSZ
+    /// numbers, the standard catalogue of codes and one job per available CPU.
+    pub fn new(output: &Path) -> Self {
+        Batch {
+            output: output.to_path_buf(),
+            mapping: Mapping::standaard(),
+            tolerantie: Tolerantie::default(),
+            privacy: Privacy::default(),
+            codes: Codes::standaard(),
+            jobs: thread::available_parallelism().map_or(1, |jobs| jobs.get()),
+        }
+    }
+
+    /// Reconciles the employers in parallel and writes a verschil document for each of them.
+    ///
+    /// An employer that fails (a missing or unreadable document, even a panic) gets an `Err` outcome and does not
+    /// stop the others. `progress` is called from the worker threads as soon as an employer is done, the
+    /// results are returned in the order of `employers`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BatchError::WriteError` if the output folder can not be created.
+    pub fn run(&self, employers: Vec<Employer>, progress: impl Fn(&BatchResult) + Sync) -> Result<Vec<BatchResult>, BatchError> {
+        fs::create_dir_all(&self.output).map_err(|_| BatchError::WriteError(self.output.display().to_string()))?;
+
+        let next = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<BatchResult>>> = Mutex::new(employers.iter().map(|_| None).collect());
+
+        thread::scope(|scope| {
+            for _ in 0..self.jobs.clamp(1, employers.len().max(1)) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(employer) = employers.get(index) else {
+                        break;
+                    };
+                    let outcome = panic::catch_unwind(AssertUnwindSafe(|| self.reconcile(employer)))
+                        .unwrap_or_else(|panic| Err(BatchError::Panic(panic_message(panic.as_ref()))));
+                    let result = BatchResult { employer: employer.clone(), outcome };
+                    progress(&result);
+                    results.lock().unwrap_or_else(|e| e.into_inner())[index] = Some(result);
+                });
+            }
+        });
+
+        Ok(results.into_inner()
+            .unwrap_or_else(|e| e.into_inner())
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+
+    /// Compares the DMFA of one employer with its loonbestand and writes the verschil document.
+    fn reconcile(&self, employer: &Employer) -> Result<Outcome, BatchError> {
+        let dmfa = employer.dmfa.as_ref().ok_or(BatchError::DmfaNotFound)?;
+        let (source, verschillen, niet_gemapt) = match (&employer.cipal, &employer.bosa) {
+            (Some(cipal), None) => {
+                let vergelijking = compare_dmfa_cipal(dmfa, cipal, &self.mapping)?;
+                ("CIPAL", vergelijking.verschillen, vergelijking.niet_gemapt)
+            }
+            (None, Some(bosa)) => ("BOSA", compare_dmfa_bosa(dmfa, bosa)?, Vec::new()),
+            (None, None) => return Err(BatchError::LoonbestandNotFound),
+            (Some(_), Some(_)) => return Err(BatchError::MultipleLoonbestanden),
+        };
+
+        let report = self.output.join(format!("{}-verschil.xlsx", employer.kbo));
+        let mut verschil_report = VerschilReport::new(&verschillen);
+        verschil_report.privacy = self.privacy.clone();
+        verschil_report.codes = self.codes.clone();
+        verschil_report.write(&report.to_string_lossy())?;
+
+        Ok(Outcome { source, verschillen, niet_gemapt, report })
+    }
+
+    /// The consolidated index : one row per employer with its totals, or the reason it failed.
+    pub fn index(&self, results: &[BatchResult]) -> Table {
+        let mut table = Table::new(&[
+            ("kbo", ValueType::Text),
+            ("bron", ValueType::Text),
+            ("status", ValueType::Text),
+            ("verschillen", ValueType::Integer),
+            ("materieel", ValueType::Integer),
+            ("loonbestand", ValueType::Amount),
+            ("dmfa", ValueType::Amount),
+            ("verschil", ValueType::Amount),
+            ("niet_gemapt", ValueType::Integer),
+            ("report", ValueType::Text),
+            ("fout", ValueType::Text),
+        ]);
+        for result in results {
+            let kbo = Value::Text(result.employer.kbo.clone());
+            table.rows.push(match &result.outcome {
+                Ok(outcome) => {
+                    let verschillen = &outcome.verschillen;
+                    let material = self.tolerantie.telling(verschillen).get(&Klasse::Materieel).copied().unwrap_or_default();
+                    vec![
+                        kbo,
+                        Value::Text(outcome.source.to_string()),
+                        Value::Text("ok".to_string()),
+                        Value::Integer(verschillen.len() as i64),
+                        Value::Integer(material as i64),
+                        Value::Amount(verschillen.iter().map(|verschil| verschil.loonbestand).sum()),
+                        Value::Amount(verschillen.iter().map(|verschil| verschil.dmfa).sum()),
+                        Value::Amount(verschillen.iter().map(Verschil::verschil).sum::<Money>()),
+                        Value::Integer(outcome.niet_gemapt.len() as i64),
+                        Value::Text(outcome.report.display().to_string()),
+                        Value::Empty,
+                    ]
+                }
+                Err(error) => vec![
+                    kbo,
+                    Value::Empty,
+                    Value::Text("fout".to_string()),
+                    Value::Empty,
+                    Value::Empty,
+                    Value::Empty,
+                    Value::Empty,
+                    Value::Empty,
+                    Value::Empty,
+                    Value::Empty,
+                    Value::Text(error.to_string()),
+                ],
+            });
+        }
+        table
+    }
+}
+
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    panic.downcast_ref::<&str>().copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown cause")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A folder with one complete employer, one without loonbestand, one with an invalid DMFA document and a
+    /// document whose prefix is not a KBO number.
+    fn folder() -> TempDir {
+        let folder = tempfile::tempdir().unwrap();
+        for (fixture, name) in [
+            ("207527540-dmfa.xlsx", "207527540-dmfa.xlsx"),
+            ("207527540-cipal.xlsx", "0207527540-CIPAL.xlsx"),
+            ("207527540-verschil.xlsx", "207527540-verschil.xlsx"),
+            ("208322049-dmfa.xlsx", "208322049-dmfa.xlsx"),
+            ("207527540-cipal.xlsx", "0123456749-cipal.xlsx"),
+            ("207527540-cipal.xlsx", "0123456789-cipal.xlsx"),
+        ] {
+            fs::copy(Path::new("tests/fixtures").join(fixture), folder.path().join(name)).unwrap();
+        }
+        fs::write(folder.path().join("123456749-dmfa.xlsx"), "not a workbook").unwrap();
+        fs::write(folder.path().join("notities.txt"), "").unwrap();
+        folder
+    }
+
+    #[test]
+    fn test_scan() {
+        let folder = folder();
+        let scan = scan(folder.path()).unwrap();
+        let kbos: Vec<&str> = scan.employers.iter().map(|employer| employer.kbo.as_str()).collect();
+        assert_eq!(kbos, vec!["0123456749", "0207527540", "0208322049"]);
+        assert_eq!(scan.employers[0].dmfa, Some(folder.path().join("123456749-dmfa.xlsx")));
+        assert_eq!(scan.employers[1].dmfa, Some(folder.path().join("207527540-dmfa.xlsx")));
+        assert_eq!(scan.employers[1].cipal, Some(folder.path().join("0207527540-CIPAL.xlsx")));
+        assert_eq!(scan.employers[2].cipal, None);
+        assert_eq!(scan.employers[2].bosa, None);
+        assert_eq!(scan.invalid, vec![(folder.path().join("0123456789-cipal.xlsx"), KboError::InvalidControl)]);
+    }
+
+    #[test]
+    fn test_scan_directory_not_found() {
+        assert_eq!(scan(Path::new("tests/fixtures/nonexistent")), Err(BatchError::DirectoryNotFound("tests/fixtures/nonexistent".to_string())));
+    }
+
+    #[test]
+    fn test_run() {
+        let folder = folder();
+        let output = folder.path().join("verschillen");
+        let mut batch = Batch::new(&output);
+        batch.jobs = 2;
+
+        let done = AtomicUsize::new(0);
+        let results = batch.run(scan(folder.path()).unwrap().employers, |_| {
+            done.fetch_add(1, Ordering::Relaxed);
+        }).unwrap();
+        assert_eq!(done.into_inner(), 3);
+        assert_eq!(results.len(), 3);
+
+        // The invalid DMFA document and the missing loonbestand do not stop the complete employer
+        assert!(matches!(results[0].outcome, Err(BatchError::Compare(CompareError::Dmfa(_)))));
+        assert_eq!(results[2].outcome, Err(BatchError::LoonbestandNotFound));
+        let outcome = results[1].outcome.as_ref().unwrap();
+        assert_eq!(outcome.source, "CIPAL");
+        assert_eq!(outcome.report, output.join("0207527540-verschil.xlsx"));
+        assert!(outcome.report.exists());
+        assert!(!output.join("0123456749-verschil.xlsx").exists());
+
+        let index = batch.index(&results);
+        assert_eq!(index.rows.len(), 3);
+        assert_eq!(index.rows[1][2], Value::Text("ok".to_string()));
+        assert_eq!(index.rows[1][3], Value::Integer(outcome.verschillen.len() as i64));
+        assert_eq!(index.rows[2][2], Value::Text("fout".to_string()));
+        assert_eq!(index.rows[2][10], Value::Text("CIPAL or BOSA document not found.".to_string()));
+    }
+}
@@ -1,9 +1,100 @@
-use std::{path::Path, process};
+use std::{collections::{BTreeMap, HashSet}, fmt::Display, fs::File, io::{self, BufRead, Write}, path::Path, process};
 
-use clap::{Arg, Command, crate_version, crate_authors, ArgGroup};
-// use std::{fs::File, io::{Seek, SeekFrom}};
-use lvgl::types::DfmaReader;
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command, crate_version, crate_authors};
+use serde_json::{json, Value};
+use lvgl::{
+    compare_dmfa_bosa, compare_dmfa_cipal, detect, file_type, highlight, schema, Batch, BatchResult, BosaReader,
+    Bron, CellError, CipalReader, Codes, DmfaReader, Format, Klasse, Mapping, Money, NietGemapt, Privacy, PrivacyProfile,
+    Probleem, Rrn, RrnKind, Table, Tolerantie, Verschil, VerschilReport,
+};
 
+/// The options shared by all subcommands.
+struct Global {
+    output: Option<String>,
+    json: bool,
+    quiet: bool,
+}
+
+impl Global {
+    /// Informational messages go to stderr, so they never mix with the results.
+    fn info(&self, message: impl Display) {
+        if !self.quiet {
+            eprintln!("{}", message);
+        }
+    }
+
+    /// The results go to the --output file, or to stdout.
+    fn writer(&self) -> Box<dyn Write> {
+        match &self.output {
+            Some(output) => Box::new(fatal(File::create(output))),
+            None => Box::new(io::stdout()),
+        }
+    }
+}
+
+/// What is shown of the differences, and in which order.
+struct CompareOptions<'a> {
+    privacy: Privacy,
+    export: Option<(&'a String, Format)>,
+    token_table: Option<&'a String>,
+    tolerance: Tolerantie,
+    class: Klasse,
+    sort: &'a str,
+    codes: Codes,
+}
+
+/// A document of one of the sources.
+enum Document {
+    Dmfa(DmfaReader),
+    Cipal(CipalReader),
+    Bosa(BosaReader),
+}
+
+impl Document {
+    /// Opens the document with the reader of the source recognised from its content.
+    fn open(file: &str) -> Result<Document, String> {
+        let error = |e: &dyn Display| format!("{}: {}", file, e);
+        match detect(Path::new(file)).map_err(|e| error(&e))? {
+            Bron::Dmfa => DmfaReader::new(file).map(Document::Dmfa).map_err(|e| error(&e)),
+            Bron::Cipal => CipalReader::new(file).map(Document::Cipal).map_err(|e| error(&e)),
+            Bron::Bosa => BosaReader::new(file).map(Document::Bosa).map_err(|e| error(&e)),
+            Bron::Verschil => Err(format!("{} is a verschil document, not a DMFA, CIPAL or BOSA document.", file)),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Document::Dmfa(_) => "DMFA",
+            Document::Cipal(_) => "CIPAL",
+            Document::Bosa(_) => "BOSA",
+        }
+    }
+
+    /// Reads all wage lines as a flat table. A lenient read skips the lines with invalid cells and returns
+    /// these cells, a strict read stops at the first one.
+    fn table(&self, privacy: &Privacy, lenient: bool) -> Result<(Table, Vec<CellError>), String> {
+        match self {
+            Document::Dmfa(reader) if lenient => reader.data_lenient()
+                .map(|(data, errors)| (Table::dmfa(&data, privacy), errors))
+                .map_err(|e| e.to_string()),
+            Document::Dmfa(reader) => reader.data()
+                .map(|data| (Table::dmfa(&data, privacy), Vec::new()))
+                .map_err(|e| e.to_string()),
+            Document::Cipal(reader) if lenient => reader.data_lenient()
+                .map(|(data, errors)| (Table::cipal(&data, privacy), errors))
+                .map_err(|e| e.to_string()),
+            Document::Cipal(reader) => reader.data()
+                .map(|data| (Table::cipal(&data, privacy), Vec::new()))
+                .map_err(|e| e.to_string()),
+            Document::Bosa(reader) if lenient => reader.data_lenient()
+                .map(|(data, errors)| (Table::bosa(&data, privacy), errors))
+                .map_err(|e| e.to_string()),
+            Document::Bosa(reader) => reader.data()
+                .map(|data| (Table::bosa(&data, privacy), Vec::new()))
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
 
 fn main() {
     let description = env!("CARGO_PKG_DESCRIPTION");
@@ -12,10 +103,105 @@ fn main() {
         .version(crate_version!())
         .author(crate_authors!("\n"))
         .about(description)
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .arg(Arg::new("output")
+            .short('o')
+            .long("output")
+            .global(true)
+            .help("Path to write the result to instead of stdout, an .xlsx path gets the verschil document of compare."),
+        )
+        .arg(Arg::new("format")
+            .short('f')
+            .long("format")
+            .global(true)
+            .value_parser(["text", "json"])
+            .default_value("text")
+            .help("Format of the result."),
+        )
+        .arg(Arg::new("quiet")
+            .short('q')
+            .long("quiet")
+            .global(true)
+            .action(ArgAction::SetTrue)
+            .help("Do not print informational messages and warnings."),
+        )
+        .subcommand(compare_command())
+        .subcommand(batch_command())
+        .subcommand(Command::new("inspect")
+            .about("Shows the file type and source of a document, and what a DMFA, CIPAL or BOSA document holds.")
+            .arg(Arg::new("file").required(true).help("Path to the document in XLSX format.")),
+        )
+        .subcommand(Command::new("validate")
+            .about("Checks a document against the schema of its format and reads all its lines, exits with 1 if the document is invalid.")
+            .arg(Arg::new("file").required(true).help("Path to the document in XLSX format."))
+            .arg(Arg::new("schema")
+                .long("schema")
+                .value_parser(["dmfa", "cipal", "bosa", "verschil"])
+                .help("The format of the document, recognised from its content by default."),
+            )
+            .arg(Arg::new("highlight")
+                .long("highlight")
+                .value_name("XLSX")
+                .help("Path to write a copy of the document to, with the invalid cells highlighted."),
+            ),
+        )
+        .subcommand(Command::new("convert")
+            .about("Converts the lines of a DMFA, CIPAL or BOSA document.")
+            .arg(Arg::new("file").required(true).help("Path to the document in XLSX format."))
+            .arg(Arg::new("to")
+                .long("to")
+                .required(true)
+                .value_parser(["csv", "ndjson", "parquet", "json"])
+                .help("Format to convert to, written next to the document unless --output is given."),
+            )
+            .arg(Arg::new("lenient")
+                .long("lenient")
+                .action(ArgAction::SetTrue)
+                .help("Skip the lines with invalid cells instead of stopping, with a warning for each cell."),
+            )
+            .arg(privacy_arg()),
+        )
+        .subcommand(Command::new("rrn")
+            .about("INSZ (rijksregister and BIS) numbers.")
+            .subcommand_required(true)
+            .subcommand(Command::new("check")
+                .about("Checks INSZ numbers, read from stdin (one per line) if none are given. Exits with 1 if one is invalid.")
+                .arg(Arg::new("insz").num_args(0..).help("INSZ numbers, e.g. 69.10.01-363.59.")),
+            ),
+        )
+        .get_matches();
+
+    let global = Global {
+        output: matches.get_one::<String>("output").cloned(),
+        json: matches.get_one::<String>("format").is_some_and(|format| format == "json"),
+        quiet: matches.get_flag("quiet"),
+    };
+
+    match matches.subcommand() {
+        Some(("compare", matches)) => compare(matches, &global),
+        Some(("batch", matches)) => batch(matches, &global),
+        Some(("inspect", matches)) => inspect(matches, &global),
+        Some(("validate", matches)) => validate(matches, &global),
+        Some(("convert", matches)) => convert(matches, &global),
+        Some(("rrn", matches)) => match matches.subcommand() {
+            Some(("check", matches)) => rrn_check(matches, &global),
+            _ => unreachable!("clap requires a subcommand"),
+        },
+        _ => unreachable!("clap requires a subcommand"),
+    }
+}
+
+fn compare_command() -> Command {
+    Command::new("compare")
+        .about("Compares the DMFA with the BOSA or CIPAL loonbestand.")
+        .arg(Arg::new("files")
+            .num_args(0..=2)
+            .help("Paths to the DMFA and the BOSA or CIPAL document in XLSX format, in any order, recognised from their content."),
+        )
         .arg(Arg::new("dmfa.xlsx")
             .short('d')
             .long("dmfa")
-            .required(true)
             .help("Path to the DMFA document in XLSX format."),
         )
         .arg(Arg::new("bosa.xlsx")
@@ -28,79 +214,584 @@ fn main() {
             .long("cipal")
             .help("Path to the CIPAL document in XLSX format."),
         )
+        .arg(Arg::new("mapping")
+            .short('m')
+            .long("mapping")
+            .help("Path to the CIPAL → DMFA looncode mapping in TOML or CSV format."),
+        )
+        .arg(privacy_arg())
+        .arg(codes_arg())
+        .arg(Arg::new("export")
+            .long("export")
+            .help("Path to write the differences to as a table, in the format of the extension (.csv, .ndjson, .parquet or .json)."),
+        )
+        .arg(Arg::new("token-table")
+            .long("token-table")
+            .help("Path to write the token ↔ INSZ table in CSV format, to be stored apart from the reports."),
+        )
+        .arg(Arg::new("tolerance-file")
+            .long("tolerance-file")
+            .help("Path to the tolerance settings in TOML format (absoluut, relatief, materialiteit and [[looncode]] overrides)."),
+        )
+        .arg(Arg::new("tolerance")
+            .long("tolerance")
+            .value_parser(clap::value_parser!(Money))
+            .help("Absolute tolerance per line in euro, e.g. 0.05."),
+        )
+        .arg(Arg::new("tolerance-pct")
+            .long("tolerance-pct")
+            .value_parser(clap::value_parser!(f64))
+            .help("Relative tolerance per line, as a percentage of the largest amount."),
+        )
+        .arg(Arg::new("materiality")
+            .long("materiality")
+            .value_parser(clap::value_parser!(Money))
+            .help("Materiality per person and quarter in euro, smaller differences are minor."),
+        )
+        .arg(Arg::new("class")
+            .long("class")
+            .value_parser(["within", "minor", "material"])
+            .default_value("within")
+            .help("Only output the differences of at least this class."),
+        )
+        .arg(Arg::new("sort")
+            .long("sort")
+            .value_parser(["key", "class", "amount"])
+            .default_value("key")
+            .help("Sort the differences by key (INSZ, Kwart, WGC, WNK, LC), by class or by amount, largest first."),
+        )
         .group(ArgGroup::new("exclusive")
-            .args(&["bosa.xlsx", "cipal.xlsx"])
+            .args(["bosa.xlsx", "cipal.xlsx"])
+        )
+}
+
+fn batch_command() -> Command {
+    Command::new("batch")
+        .about("Compares the DMFA of every employer in a folder with its CIPAL or BOSA loonbestand.")
+        .arg(Arg::new("directory")
             .required(true)
+            .help("Folder with the <kbo>-dmfa.xlsx, <kbo>-cipal.xlsx and <kbo>-bosa.xlsx documents."),
         )
-        .get_matches();
+        .arg(Arg::new("reports")
+            .long("reports")
+            .help("Folder to write the <kbo>-verschil.xlsx documents to, <directory>/verschillen by default."),
+        )
+        .arg(Arg::new("index")
+            .long("index")
+            .help("Path to write the index of totals and failures to, in the format of the extension (index.csv in the reports folder by default)."),
+        )
+        .arg(Arg::new("jobs")
+            .short('j')
+            .long("jobs")
+            .value_parser(clap::value_parser!(usize))
+            .help("Number of employers compared at the same time, one per CPU by default."),
+        )
+        .arg(Arg::new("mapping")
+            .short('m')
+            .long("mapping")
+            .help("Path to the CIPAL → DMFA looncode mapping in TOML or CSV format."),
+        )
+        .arg(Arg::new("tolerance-file")
+            .long("tolerance-file")
+            .help("Path to the tolerance settings in TOML format, used to count the material differences."),
+        )
+        .arg(privacy_arg())
+        .arg(codes_arg())
+}
 
-        let dmfa = matches.get_one::<String>("dmfa.xlsx").unwrap();
-        let bosa = matches.get_one::<String>("bosa.xlsx");
-        let cipal = matches.get_one::<String>("cipal.xlsx");
-    
-        let dfma_reader = DfmaReader::new(&dmfa);
+fn privacy_arg() -> Arg {
+    Arg::new("privacy")
+        .long("privacy")
+        .value_parser(["clear", "masked", "tokenised", "tokenized"])
+        .default_value("masked")
+        .help("How INSZ numbers appear in the output, masked unless clear is asked for, the key of the tokens is read from LVGL_PRIVACY_KEY.")
+}
 
-        let dmfa_path = Path::new(&dmfa);
+fn codes_arg() -> Arg {
+    Arg::new("codes")
+        .long("codes")
+        .help("Path to the catalogue of WGC, WNK and LC codes in TOML format, the embedded catalogue by default.")
+}
 
-        match is_valid(dmfa_path) {
-            Ok(true) => (),
-            Ok(false) => {
-                eprintln!("DMFA document must be in XLSX format.");
-                process::exit(1);
-            }
-            Err(e) => {
-                eprintln!("Error: {}", e);
-                process::exit(1);
-            }
+fn codes(matches: &ArgMatches) -> Codes {
+    match matches.get_one::<String>("codes") {
+        Some(codes) => fatal(Codes::load(codes)),
+        None => Codes::standaard(),
+    }
+}
+
+fn privacy(matches: &ArgMatches) -> Privacy {
+    let profile: PrivacyProfile = fatal(matches.get_one::<String>("privacy").unwrap().parse());
+    let key = std::env::var("LVGL_PRIVACY_KEY").unwrap_or_default();
+    fatal(Privacy::new(profile, key.as_bytes()))
+}
+
+/// Prints the error and exits with 1.
+fn fatal<T, E: Display>(result: Result<T, E>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
         }
+    }
+}
 
-        if let Some(bosa) = bosa {
-            let bosa_path = Path::new(&bosa);
-            match is_valid(bosa_path) {
-                Ok(true) => (),
-                Ok(false) => {
-                    eprintln!("BOSA document must be in XLSX format.");
-                    process::exit(1);
-                }
-                Err(e) => {
-                    eprintln!("Error: {}", e);
-                    process::exit(1);
-                }
+/// The documents to compare : given with their flag, or recognised from their content.
+fn documents(matches: &ArgMatches) -> (String, Option<String>, Option<String>) {
+    let mut dmfa = matches.get_one::<String>("dmfa.xlsx").cloned();
+    let mut bosa = matches.get_one::<String>("bosa.xlsx").cloned();
+    let mut cipal = matches.get_one::<String>("cipal.xlsx").cloned();
+
+    for file in matches.get_many::<String>("files").into_iter().flatten() {
+        let source = fatal(detect(Path::new(file)).map_err(|e| format!("{}: {}", file, e)));
+        let document = match source {
+            Bron::Dmfa => &mut dmfa,
+            Bron::Bosa => &mut bosa,
+            Bron::Cipal => &mut cipal,
+            Bron::Verschil => fatal(Err(format!("{} is a verschil document, it can not be compared.", file))),
+        };
+        if let Some(other) = document {
+            fatal(Err(format!("{} and {} are both {} documents.", other, file, source)))
+        }
+        *document = Some(file.clone());
+    }
+
+    let dmfa = fatal(dmfa.ok_or("No DMFA document, give it as argument or with --dmfa."));
+    if bosa.is_some() == cipal.is_some() {
+        fatal(Err("Give either a BOSA or a CIPAL document."))
+    }
+    if cipal.is_none() && matches.contains_id("mapping") {
+        fatal(Err("The looncode mapping is only used with a CIPAL document."))
+    }
+    (dmfa, bosa, cipal)
+}
+
+fn compare(matches: &ArgMatches, global: &Global) {
+    let (dmfa, bosa, cipal) = documents(matches);
+    let mapping = match matches.get_one::<String>("mapping") {
+        Some(mapping) => fatal(Mapping::load(mapping)),
+        None => Mapping::standaard(),
+    };
+    let export = matches.get_one::<String>("export").map(|export| (export, fatal(Format::from_path(export))));
+    let mut tolerance = match matches.get_one::<String>("tolerance-file") {
+        Some(tolerance) => fatal(Tolerantie::load(tolerance)),
+        None => Tolerantie::default(),
+    };
+    if let Some(absolute) = matches.get_one::<Money>("tolerance") {
+        tolerance.absoluut = *absolute;
+    }
+    if let Some(relative) = matches.get_one::<f64>("tolerance-pct") {
+        tolerance.relatief = *relative;
+    }
+    if let Some(materiality) = matches.get_one::<Money>("materiality") {
+        tolerance.materialiteit = *materiality;
+    }
+    let options = CompareOptions {
+        privacy: privacy(matches),
+        export,
+        token_table: matches.get_one::<String>("token-table"),
+        tolerance,
+        class: fatal(matches.get_one::<String>("class").unwrap().parse()),
+        sort: matches.get_one::<String>("sort").unwrap(),
+        codes: codes(matches),
+    };
+
+    // The readers check that every document is an xlsx workbook, whatever its extension
+    let dmfa_path = Path::new(&dmfa);
+
+    if let Some(bosa) = bosa {
+        let bosa_path = Path::new(&bosa);
+        global.info(format!("DMFA-BOSA: {} ↔ {}", dmfa, bosa));
+        let verschillen = fatal(compare_dmfa_bosa(dmfa_path, bosa_path));
+        report(&verschillen, &[], &options, global);
+    }
+    if let Some(cipal) = cipal {
+        let cipal_path = Path::new(&cipal);
+        global.info(format!("DMFA-CIPAL: {} ↔ {}", dmfa, cipal));
+        let vergelijking = fatal(compare_dmfa_cipal(dmfa_path, cipal_path, &mapping));
+        report(&vergelijking.verschillen, &vergelijking.niet_gemapt, &options, global);
+    }
+}
+
+fn batch(matches: &ArgMatches, global: &Global) {
+    let directory = Path::new(matches.get_one::<String>("directory").unwrap());
+    let reports = matches.get_one::<String>("reports")
+        .map_or_else(|| directory.join("verschillen"), |reports| Path::new(reports).to_path_buf());
+    let index = matches.get_one::<String>("index")
+        .map_or_else(|| reports.join("index.csv").to_string_lossy().to_string(), String::clone);
+    let format = fatal(Format::from_path(&index));
+
+    let mut batch = Batch::new(&reports);
+    if let Some(mapping) = matches.get_one::<String>("mapping") {
+        batch.mapping = fatal(Mapping::load(mapping));
+    }
+    if let Some(tolerance) = matches.get_one::<String>("tolerance-file") {
+        batch.tolerantie = fatal(Tolerantie::load(tolerance));
+    }
+    if let Some(jobs) = matches.get_one::<usize>("jobs") {
+        batch.jobs = *jobs;
+    }
+    batch.privacy = privacy(matches);
+    batch.codes = codes(matches);
+
+    let scan = fatal(lvgl::batch::scan(directory));
+    for (path, error) in &scan.invalid {
+        global.info(format!("Warning: {} is skipped, its prefix is not a valid KBO number : {}", path.display(), error));
+    }
+    global.info(format!("{} employers in {}", scan.employers.len(), directory.display()));
+    let results = fatal(batch.run(scan.employers, |result: &BatchResult| match &result.outcome {
+        Ok(outcome) => global.info(format!("{} DMFA-{}: {} differences", result.employer.kbo, outcome.source, outcome.verschillen.len())),
+        Err(error) => global.info(format!("{} failed: {}", result.employer.kbo, error)),
+    }));
+
+    let table = batch.index(&results);
+    fatal(table.write(&index, format));
+    global.info(format!("Index: {}", index));
+
+    let mut writer = global.writer();
+    if global.json {
+        fatal(table.write_to(&mut writer, Format::Json));
+    } else {
+        fatal(print_batch(&mut writer, &results, &batch.tolerantie));
+    }
+
+    if !scan.invalid.is_empty() || results.iter().any(|result| result.outcome.is_err()) {
+        process::exit(1);
+    }
+}
+
+fn print_batch(writer: &mut dyn Write, results: &[BatchResult], tolerance: &Tolerantie) -> io::Result<()> {
+    writeln!(writer, "{:<12} {:<5} {:>11} {:>9} {:>14}  Fout", "KBO", "Bron", "Verschillen", "Materieel", "Verschil")?;
+    for result in results {
+        match &result.outcome {
+            Ok(outcome) => {
+                let material = tolerance.telling(&outcome.verschillen).get(&Klasse::Materieel).copied().unwrap_or_default();
+                let verschil: Money = outcome.verschillen.iter().map(Verschil::verschil).sum();
+                writeln!(writer, "{:<12} {:<5} {:>11} {:>9} {:>14}", result.employer.kbo, outcome.source,
+                    outcome.verschillen.len(), material, verschil)?;
             }
-            println!("DMFA-BOSA: {} ↔ {}", dmfa, bosa);
-            compare_dmfa_bosa(dmfa_path, bosa_path);
+            Err(error) => writeln!(writer, "{:<12} {:<5} {:>11} {:>9} {:>14}  {}", result.employer.kbo, "", "", "", "", error)?,
         }
-        if let Some(cipal) = cipal {
-            let cipal_path = Path::new(&cipal);
-            match is_valid(cipal_path) {
-                Ok(true) => (),
-                Ok(false) => {
-                    eprintln!("CIPAL document must be in XLSX format.");
-                    process::exit(1);
-                }
-                Err(e) => {
-                    eprintln!("Error: {}", e);
-                    process::exit(1);
-                }
+    }
+    let failed = results.iter().filter(|result| result.outcome.is_err()).count();
+    writeln!(writer, "Werkgevers: {} ok, {} mislukt", results.len() - failed, failed)
+}
+
+fn report(verschillen: &[Verschil], niet_gemapt: &[NietGemapt], options: &CompareOptions, global: &Global) {
+    let privacy = &options.privacy;
+    for line in niet_gemapt {
+        global.info(format!("Warning: CIPAL bezoldigingscode {} (code {}, aard {}, {}) is not mapped : {} lines, {} not compared.",
+            line.bezoldigingscode, line.code, line.aard, line.omschrijving, line.lijnen, line.brutobedrag));
+    }
+    let code_errors = options.codes.controleer(verschillen.iter().map(|verschil| &verschil.key));
+    for error in &code_errors {
+        global.info(format!("Warning: {}", error));
+    }
+
+    let classified = classify(verschillen, options);
+    let counts = options.tolerance.telling(verschillen);
+    let verschillen: Vec<Verschil> = classified.iter().map(|(verschil, _)| verschil.clone()).collect();
+
+    // An .xlsx output is the verschil document, the differences are then still printed on stdout
+    let document = global.output.as_ref().filter(|output| output.to_lowercase().ends_with(".xlsx"));
+    let mut writer: Box<dyn Write> = match document {
+        Some(_) => Box::new(io::stdout()),
+        None => global.writer(),
+    };
+    if global.json {
+        let json = json!({
+            "verschillen": classified.iter().map(|(verschil, klasse)| verschil_json(verschil, *klasse, privacy)).collect::<Vec<_>>(),
+            "niet_gemapt": niet_gemapt.iter().map(|line| json!({
+                "bezoldigingscode": line.bezoldigingscode,
+                "code": line.code,
+                "aard": line.aard,
+                "omschrijving": line.omschrijving,
+                "lijnen": line.lijnen,
+                "brutobedrag": line.brutobedrag.to_f64(),
+            })).collect::<Vec<_>>(),
+            "onbekende_codes": code_errors.iter().map(|error| error.to_string()).collect::<Vec<_>>(),
+            "telling": counts.iter().map(|(klasse, count)| (klasse.to_string(), json!(count))).collect::<serde_json::Map<_, _>>(),
+        });
+        fatal(writeln!(writer, "{:#}", json));
+    } else {
+        fatal(print_verschillen(&mut writer, &classified, privacy));
+        fatal(print_counts(&mut writer, &counts));
+    }
+
+    if let Some(output) = document {
+        let mut report = VerschilReport::new(&verschillen);
+        report.privacy = privacy.clone();
+        report.codes = options.codes.clone();
+        fatal(report.write(output));
+        global.info(format!("Verschil: {}", output));
+    }
+    if let Some((export, format)) = options.export {
+        fatal(Table::verschillen(&classified, privacy).write(export, format));
+        global.info(format!("Export: {}", export));
+    }
+    if let Some(token_table) = options.token_table {
+        fatal(privacy.write_token_table(token_table, verschillen.iter().map(|verschil| &verschil.key.insz)));
+        global.info(format!("Tokens: {}", token_table));
+    }
+}
+
+fn verschil_json(verschil: &Verschil, klasse: Klasse, privacy: &Privacy) -> Value {
+    let key = &verschil.key;
+    json!({
+        "kwart": key.kwart.to_string(),
+        "wgc": key.wgc,
+        "wnk": key.wnk,
+        "insz": privacy.insz(&key.insz),
+        "lc": key.lc,
+        "loonbestand": verschil.loonbestand.to_f64(),
+        "dmfa": verschil.dmfa.to_f64(),
+        "verschil": verschil.verschil().to_f64(),
+        "klasse": klasse.to_string(),
+    })
+}
+
+/// Classifies the differences, keeps those of at least the requested class and sorts them.
+fn classify(verschillen: &[Verschil], options: &CompareOptions) -> Vec<(Verschil, Klasse)> {
+    let mut classified: Vec<(Verschil, Klasse)> = verschillen.iter().cloned()
+        .zip(options.tolerance.klassen(verschillen))
+        .filter(|(_, klasse)| *klasse >= options.class)
+        .collect();
+
+    // The differences come sorted by key, and the sorts are stable
+    match options.sort {
+        "class" => classified.sort_by(|(a, a_klasse), (b, b_klasse)| {
+            b_klasse.cmp(a_klasse).then_with(|| b.verschil().abs().cmp(&a.verschil().abs()))
+        }),
+        "amount" => classified.sort_by_key(|(verschil, _)| std::cmp::Reverse(verschil.verschil().abs())),
+        _ => (),
+    }
+    classified
+}
+
+fn print_verschillen(writer: &mut dyn Write, verschillen: &[(Verschil, Klasse)], privacy: &Privacy) -> io::Result<()> {
+    writeln!(writer, "{:>5} {:>4} {:>4} {:>17} {:>3} {:>12} {:>12} {:>12} {:>9}",
+        "Kwart", "WGC", "WNK", "INSZ", "LC", "Loonbestand", "DMFA", "Verschil", "Klasse")?;
+    for (verschil, klasse) in verschillen {
+        let key = &verschil.key;
+        writeln!(writer, "{:>5} {:>4} {:>4} {:>17} {:>3} {:>12} {:>12} {:>12} {:>9}",
+            key.kwart, key.wgc, key.wnk, privacy.insz(&key.insz), key.lc, verschil.loonbestand, verschil.dmfa, verschil.verschil(), klasse)?;
+    }
+    let total: Money = verschillen.iter().map(|(verschil, _)| verschil.verschil()).sum();
+    writeln!(writer, "{:>63} {:>12}", "Totaal", total)
+}
+
+fn print_counts(writer: &mut dyn Write, counts: &BTreeMap<Klasse, usize>) -> io::Result<()> {
+    let counts: Vec<String> = [Klasse::Materieel, Klasse::Klein, Klasse::BinnenTolerantie].iter()
+        .map(|klasse| format!("{} {}", counts.get(klasse).copied().unwrap_or_default(), klasse))
+        .collect();
+    writeln!(writer, "Verschillen: {}", counts.join(", "))
+}
+
+/// Writes labelled values, as `label: value` lines or as a JSON object.
+fn print_fields(global: &Global, fields: &[(&str, Value)]) {
+    let mut writer = global.writer();
+    if global.json {
+        let object: serde_json::Map<String, Value> = fields.iter().map(|(label, value)| (label.to_string(), value.clone())).collect();
+        fatal(writeln!(writer, "{:#}", Value::Object(object)));
+    } else {
+        for (label, value) in fields {
+            let value = match value {
+                Value::String(value) => value.clone(),
+                // One item per line, under the label
+                Value::Array(items) => items.iter()
+                    .map(|item| format!("\n  - {}", item.as_str().map_or_else(|| item.to_string(), String::from)))
+                    .collect(),
+                value => value.to_string(),
+            };
+            fatal(writeln!(writer, "{:<14} {}", format!("{}:", label), value));
+        }
+    }
+}
+
+/// The number of persons and lines and the total amount of the wage lines.
+fn summary<'a>(lines: impl Iterator<Item = (&'a Rrn, Money)>) -> Vec<(&'static str, Value)> {
+    let mut persons = HashSet::new();
+    let mut count = 0;
+    let mut total = Money::ZERO;
+    for (insz, amount) in lines {
+        persons.insert(insz);
+        count += 1;
+        total += amount;
+    }
+    vec![("personen", json!(persons.len())), ("lijnen", json!(count)), ("totaal", json!(total.to_string()))]
+}
+
+fn inspect(matches: &ArgMatches, global: &Global) {
+    let file = matches.get_one::<String>("file").unwrap();
+    let mut fields = vec![("bestand", json!(file)), ("type", json!(fatal(file_type(Path::new(file))).to_string()))];
+
+    match detect(Path::new(file)) {
+        Ok(Bron::Verschil) => {
+            fields.push(("bron", json!(Bron::Verschil.to_string())));
+            return print_fields(global, &fields);
+        }
+        Ok(_) => (),
+        Err(e) => {
+            fields.push(("fout", json!(e.to_string())));
+            print_fields(global, &fields);
+            process::exit(1);
+        }
+    }
+    let document = fatal(Document::open(file));
+    fields.push(("bron", json!(document.name())));
+    match &document {
+        Document::Dmfa(reader) => {
+            fields.push(("kbo", json!(reader.kbo_nummer.to_string())));
+            fields.push(("kwartalen", json!(fatal(reader.kwartalen()).to_string())));
+            let data = fatal(reader.data());
+            let entries = data.values().flat_map(|kwartalen| kwartalen.values()).flatten();
+            fields.extend(summary(entries.map(|entry| (&entry.insz, entry.brutto_loon))));
+        }
+        Document::Cipal(reader) => {
+            let info = &reader.info;
+            fields.push(("rapport", json!(info.rapport)));
+            fields.push(("databron", json!(info.databron)));
+            fields.push(("periode", json!(format!("{} - {}", info.begindatum, info.einddatum))));
+            fields.push(("aangemaakt op", json!(info.aangemaakt_op.to_string())));
+            let data = fatal(reader.data());
+            fields.extend(summary(data.iter().map(|entry| (&entry.insz, entry.brutobedrag))));
+        }
+        Document::Bosa(reader) => {
+            fields.push(("kbo", json!(reader.kbo_nummer.to_string())));
+            let data = fatal(reader.data());
+            let entries: Vec<_> = data.values().flat_map(|kwartalen| kwartalen.values()).flatten().collect();
+            if let (Some(first), Some(last)) = (entries.iter().map(|entry| &entry.maand).min(), entries.iter().map(|entry| &entry.maand).max()) {
+                fields.push(("maanden", json!(format!("{} - {}", first, last))));
             }
-            println!("DMFA-CIPAL: {} ↔ {}", dmfa, cipal);
-            compare_dmfa_cipal(dmfa_path, cipal_path);
+            fields.extend(summary(entries.iter().map(|entry| (&entry.insz, entry.brutto_loon))));
         }
-    
+    }
+    print_fields(global, &fields);
+}
+
+fn validate(matches: &ArgMatches, global: &Global) {
+    let file = matches.get_one::<String>("file").unwrap();
+    let source = match matches.get_one::<String>("schema").map(String::as_str) {
+        Some("dmfa") => Ok(Bron::Dmfa),
+        Some("cipal") => Ok(Bron::Cipal),
+        Some("bosa") => Ok(Bron::Bosa),
+        Some(_) => Ok(Bron::Verschil),
+        None => detect(Path::new(file)).map_err(|e| e.to_string()),
+    };
+    let invalid = |fields: &mut Vec<(&str, Value)>, label: &'static str, error: Value| {
+        fields.push(("geldig", json!(false)));
+        fields.push((label, error));
+        print_fields(global, fields);
+        process::exit(1);
+    };
+
+    let mut fields = vec![("bestand", json!(file))];
+    let source = match source {
+        Ok(source) => source,
+        Err(error) => return invalid(&mut fields, "fout", json!(error)),
+    };
+    let schema = schema(source);
+    fields.push(("schema", json!(schema.naam)));
+
+    // Every missing or mistyped column at once, before the lines are read
+    let problems = match schema.validate(Path::new(file)) {
+        Ok(problems) => problems,
+        Err(e) => return invalid(&mut fields, "fout", json!(e.to_string())),
+    };
+    let missing = problems.iter().any(|problem| matches!(problem, Probleem::Ontbreekt(_)));
+    let mut problems: Vec<Value> = problems.iter().map(|problem| json!(problem.to_string())).collect();
+    if missing || (source == Bron::Verschil && !problems.is_empty()) {
+        return invalid(&mut fields, "problemen", Value::Array(problems));
+    }
+    if source == Bron::Verschil {
+        fields.push(("geldig", json!(true)));
+        return print_fields(global, &fields);
+    }
+
+    // Then every invalid cell of the lines, with its place, value and expected type
+    let (table, errors) = match Document::open(file).and_then(|document| document.table(&Privacy::default(), true)) {
+        Ok(read) => read,
+        Err(error) => return invalid(&mut fields, "fout", json!(error)),
+    };
+    if problems.is_empty() && errors.is_empty() {
+        fields.push(("geldig", json!(true)));
+        fields.push(("lijnen", json!(table.rows.len())));
+        return print_fields(global, &fields);
+    }
+    if let Some(copy) = matches.get_one::<String>("highlight") {
+        fatal(highlight(Path::new(file), &errors, Path::new(copy)));
+        global.info(format!("{} invalid cells highlighted in {}", errors.len(), copy));
+    }
+    problems.extend(errors.iter().map(|error| json!(error.to_string())));
+    invalid(&mut fields, "problemen", Value::Array(problems));
+}
+
+fn convert(matches: &ArgMatches, global: &Global) {
+    let file = matches.get_one::<String>("file").unwrap();
+    let format: Format = fatal(matches.get_one::<String>("to").unwrap().parse());
+    let document = fatal(Document::open(file));
+    let (table, errors) = fatal(document.table(&privacy(matches), matches.get_flag("lenient")));
+    for error in &errors {
+        global.info(format!("Warning: {} The line is skipped.", error));
+    }
 
+    let output = global.output.clone()
+        .unwrap_or_else(|| Path::new(file).with_extension(format.extension()).to_string_lossy().to_string());
+    fatal(table.write(&output, format));
+    global.info(format!("{} {} → {} ({} lines)", document.name(), file, output, table.rows.len()));
+}
 
+fn rrn_check(matches: &ArgMatches, global: &Global) {
+    let numbers: Vec<String> = match matches.get_many::<String>("insz") {
+        Some(numbers) => numbers.cloned().collect(),
+        None => io::stdin().lock().lines()
+            .map(fatal)
+            .filter(|line| !line.trim().is_empty())
+            .collect(),
+    };
+
+    let results: Vec<(String, Result<lvgl::RrnInfo, lvgl::RrnError>)> = numbers.into_iter()
+        .map(|number| {
+            let info = Rrn::new(&number).and_then(|rrn| rrn.info());
+            (number.trim().to_string(), info)
+        })
+        .collect();
+
+    let mut writer = global.writer();
+    if global.json {
+        let json: Vec<Value> = results.iter().map(|(number, info)| match info {
+            Ok(info) => json!({
+                "insz": number,
+                "geldig": true,
+                "soort": kind_name(&info.kind),
+                "geboortedatum": info.birth_date.map(|date| date.to_string()),
+                "geslacht": info.gender.map(|gender| gender.to_string()),
+            }),
+            Err(e) => json!({ "insz": number, "geldig": false, "fout": e.to_string() }),
+        }).collect();
+        fatal(writeln!(writer, "{:#}", Value::Array(json)));
+    } else {
+        for (number, info) in &results {
+            match info {
+                Ok(info) => {
+                    let birth_date = info.birth_date.map_or("onbekend".to_string(), |date| date.to_string());
+                    let gender = info.gender.map_or("onbekend".to_string(), |gender| gender.to_string());
+                    fatal(writeln!(writer, "{:<17} OK {:<13} {:<10} {}", number, kind_name(&info.kind), birth_date, gender));
+                }
+                Err(e) => fatal(writeln!(writer, "{:<17} ONGELDIG {}", number, e)),
+            }
+        }
+    }
 
+    if results.iter().any(|(_, info)| info.is_err()) {
+        process::exit(1);
+    }
+}
 
-    // match matches.subcommand() {
-    //     Some(("dfma", _)) => {
-    //         let dmfa = matches.value_of("dfma").unwrap();
-    //         let bosa = matches.value_of("bosa").unwrap();
-    //         println!("DMFA: {}", dmfa);
-    //         println!("BOSA: {}", bosa);
-    //     }
-    //     _ => {
-    //         eprintln!("Invalid subcommand. Use --help for more information.");
-    //         process::exit(1);
-    //     }
-    // }
-}
\ No newline at end of file
+fn kind_name(kind: &RrnKind) -> &'static str {
+    match kind {
+        RrnKind::Rijksregister => "rijksregister",
+        RrnKind::Bis { .. } => "bis",
+    }
+}
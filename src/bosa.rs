@@ -0,0 +1,334 @@
+// BOSA = FOD Beleid en Ondersteuning, loonbestand van de federale overheid
+// The export has one header row, one KBO number and Dutch formatted amounts.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use crate::bron::{require_xlsx, BronError};
+use crate::diagnose::{CellError, RowReader};
+use crate::types::{BosaMonth, Kbo, Kwartaal, Money, Rrn};
+use crate::schema::{Kolomtype, SchemaError, BOSA};
+use crate::xlsx::{Row, Rows, XlsxReader};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum BosaError {
+    #[error("Invalid filename.")]
+    InvalidFilename,
+    #[error("File not found.")]
+    FileNotFound,
+    #[error("Invalid file type {0}, expected xlsx.")]
+    InvalidFileType(String),
+    #[error("Invalid xlsx file: {0}")]
+    InvalidXlsx(String),
+    #[error("No sheets.")]
+    NoSheets,
+    #[error("KBO not found.")]
+    KboNotFound,
+    #[error("Multiple KBO numbers found.")]
+    MultipleKbo,
+    #[error("Invalid KBO")]
+    InvalidKbo,
+    #[error("Column {0} not found.")]
+    ColumnNotFound(String),
+    #[error("{0}")]
+    InvalidCell(CellError),
+}
+
+/// One wage line of the BOSA export, normalised to the shape of a DMFA wage line.
+#[derive(Debug, PartialEq)]
+pub struct BosaEntry {
+    pub kwart: Kwartaal,         // Kwartaal van de maand
+    pub maand: BosaMonth,        // Periode YYYYMM
+    pub insz: Rrn,               // Rijksregister nummer
+    pub wgc: u16,                // Werkgever cathegorie
+    pub wnk: u16,                // Werknemer kengetal
+    pub lc: u16,                 // Looncode
+    pub brutto_loon: Money,      // Brutoloon
+}
+
+/// The wage lines by INSZ and Kwartaal.
+pub type BosaData = HashMap<Rrn, HashMap<Kwartaal, Vec<BosaEntry>>>;
+
+#[derive(Debug)]
+pub struct BosaReader {
+    pub path: PathBuf,
+    pub kbo_nummer: Kbo,
+}
+
+impl BosaReader {
+    /// Creates a new `BosaReader` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - A string slice that holds the filename of the BOSA file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BosaError` if the filename is invalid, the file is not found, the file is not an xlsx workbook
+    /// whatever its extension, the KBO number is not found, invalid or multiple KBO numbers are found.
+    pub fn new(filename: &str) -> Result<Self, BosaError> {
+        if filename.is_empty() {
+            return Err(BosaError::InvalidFilename);
+        }
+
+        let path = Path::new(filename).to_path_buf();
+
+        if !path.exists() {
+            return Err(BosaError::FileNotFound);
+        }
+
+        require_xlsx(&path).map_err(|e| match e {
+            BronError::InvalidFileType(soort) => BosaError::InvalidFileType(soort.to_string()),
+            _ => BosaError::FileNotFound,
+        })?;
+
+        let mut workbook = XlsxReader::open(&path).map_err(|e| BosaError::InvalidXlsx(e.to_string()))?;
+        let mut rows = workbook.sheet(0).map_err(|_| BosaError::NoSheets)?;
+
+        let kbo_column = BOSA.zoek(&header(&mut rows)?).get("KBO").ok_or(BosaError::KboNotFound)?;
+
+        // Collect the distinct values of the KBO column, skipping the header row
+        let mut kbo_values: HashSet<String> = HashSet::new();
+        for row in rows {
+            let row = row.map_err(|e| BosaError::InvalidXlsx(e.to_string()))?;
+            let value = row.get(kbo_column).trim();
+            if !value.is_empty() && !kbo_values.contains(value) {
+                kbo_values.insert(value.to_string());
+            }
+        }
+
+        let kbo_nummers: HashSet<Kbo> = kbo_values.iter()
+            .map(|value| Kbo::new(value))
+            .collect::<Result<_, _>>()
+            .map_err(|_| BosaError::InvalidKbo)?;
+
+        let kbo_nummer = match kbo_nummers.len() {
+            0 => return Err(BosaError::KboNotFound),
+            1 => kbo_nummers.into_iter().next().ok_or(BosaError::KboNotFound)?,
+            _ => return Err(BosaError::MultipleKbo),
+        };
+
+        Ok(BosaReader { path, kbo_nummer })
+    }
+
+    /// Reads all wage lines from the BOSA file, keyed like the DMFA data by INSZ and Kwartaal.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BosaError::ColumnNotFound` with all the required columns missing from the header, and
+    /// `BosaError::InvalidCell` with the place, value and expected type of the first cell that can not be parsed.
+    pub fn data(&self) -> Result<BosaData, BosaError> {
+        self.read(false).map(|(data, _)| data)
+    }
+
+    /// Reads all wage lines like `data`, but skips the lines with cells that can not be parsed and returns
+    /// these cells.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BosaError::ColumnNotFound` with all the required columns missing from the header.
+    pub fn data_lenient(&self) -> Result<(BosaData, Vec<CellError>), BosaError> {
+        self.read(true)
+    }
+
+    fn read(&self, lenient: bool) -> Result<(BosaData, Vec<CellError>), BosaError> {
+        let mut workbook = XlsxReader::open(&self.path).map_err(|e| BosaError::InvalidXlsx(e.to_string()))?;
+        let sheet_name = workbook.sheet_names().first().map(|name| name.to_string()).ok_or(BosaError::NoSheets)?;
+        let mut rows = workbook.sheet(0).map_err(|_| BosaError::NoSheets)?;
+
+        let kolommen = BOSA.kolommen(&header(&mut rows)?).map_err(|e| match e {
+            SchemaError::ColumnsNotFound(namen) => BosaError::ColumnNotFound(namen.join(", ")),
+            _ => BosaError::FileNotFound,
+        })?;
+        let column = |name: &str| {
+            kolommen.get(name).ok_or_else(|| BosaError::ColumnNotFound(name.to_string()))
+        };
+
+        let periode_col = column("Periode")?;
+        let insz_col = column("INSZ")?;
+        let wgc_col = column("WGC")?;
+        let wnk_col = column("WNK")?;
+        let lc_col = column("Looncode")?;
+        let bedrag_col = column("Bedrag")?;
+
+        let mut data: HashMap<Rrn, HashMap<Kwartaal, Vec<BosaEntry>>> = HashMap::new();
+        let mut errors = Vec::new();
+
+        for row in rows {
+            let row = row.map_err(|e| BosaError::InvalidXlsx(e.to_string()))?;
+            if row.get(insz_col).trim().is_empty() {
+                continue;
+            }
+
+            let mut cells = RowReader::new(&row, &self.path, &sheet_name);
+            let maand = cells.read(periode_col, "Periode", Kolomtype::Maand, |waarde| BosaMonth::new(waarde.to_string()).ok());
+            let insz = cells.read(insz_col, "INSZ", Kolomtype::Insz, |waarde| Rrn::new_checked(waarde).ok());
+            let wgc = cells.value(wgc_col, "WGC", Kolomtype::Geheel);
+            let wnk = cells.value(wnk_col, "WNK", Kolomtype::Geheel);
+            let lc = cells.value(lc_col, "Looncode", Kolomtype::Geheel);
+            let brutto_loon = cells.value(bedrag_col, "Bedrag", Kolomtype::Bedrag);
+            cells.finish(lenient, &mut errors).map_err(BosaError::InvalidCell)?;
+
+            let entry = (|| {
+                let maand = maand?;
+                Some(BosaEntry {
+                    kwart: maand.to_kwartaal(),
+                    maand,
+                    insz: insz?,
+                    wgc: wgc?,
+                    wnk: wnk?,
+                    lc: lc?,
+                    brutto_loon: brutto_loon?,
+                })
+            })();
+            if let Some(entry) = entry {
+                data.entry(entry.insz.clone()).or_default().entry(entry.kwart.clone()).or_default().push(entry);
+            }
+        }
+
+        Ok((data, errors))
+    }
+}
+
+/// The header row, the first row of the sheet.
+fn header(rows: &mut Rows) -> Result<Vec<Row>, BosaError> {
+    rows.take(1).collect::<Result<_, _>>().map_err(|e| BosaError::InvalidXlsx(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::Builder;
+    use crate::test_util::{bosa_file, broken_xlsx};
+
+    #[test]
+    fn test_bosa_reader_new_valid() {
+        let file = bosa_file(&[
+            ["208322049", "80080703041", "202310", "1", "495", "2", "811,48"],
+            ["208322049", "80080703041", "202311", "1", "495", "2", "1.622,95"],
+        ]);
+        let bosa_reader = BosaReader::new(file.path().to_str().unwrap()).unwrap();
+        assert_eq!(bosa_reader.kbo_nummer.to_btw(), "BE0208322049");
+    }
+
+    #[test]
+    fn test_bosa_reader_new_invalid_file_type() {
+        let file = Builder::new().suffix(".xlsx").tempfile().unwrap();
+        std::fs::write(file.path(), b"PK\x03\x04").unwrap();
+        let bosa_reader = BosaReader::new(file.path().to_str().unwrap());
+        assert_eq!(bosa_reader.unwrap_err(), BosaError::InvalidFileType("zip".to_string()));
+    }
+
+    #[test]
+    fn test_bosa_reader_new_other_extension() {
+        // The content decides, not the extension
+        let file = tempfile::Builder::new().suffix(".dat").tempfile().unwrap();
+        let bosa = bosa_file(&[["208322049", "80080703041", "202310", "1", "495", "2", "811,48"]]);
+        std::fs::copy(bosa.path(), file.path()).unwrap();
+        assert!(BosaReader::new(file.path().to_str().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_bosa_reader_new_file_not_found() {
+        let bosa_reader = BosaReader::new("tests/fixtures/nonexistent.xlsx");
+        assert_eq!(bosa_reader.unwrap_err(), BosaError::FileNotFound);
+    }
+
+    #[test]
+    fn test_bosa_reader_new_invalid_xlsx() {
+        let file = broken_xlsx();
+        let bosa_reader = BosaReader::new(file.path().to_str().unwrap());
+        let expected = "Invalid xlsx file: xl/_rels/workbook.xml.rels not found";
+        assert_eq!(bosa_reader.unwrap_err(), BosaError::InvalidXlsx(expected.to_string()));
+    }
+
+    #[test]
+    fn test_bosa_reader_new_multiple_kbo() {
+        let file = bosa_file(&[
+            ["208322049", "80080703041", "202310", "1", "495", "2", "811,48"],
+            ["207527540", "80080703041", "202311", "1", "495", "2", "1.622,95"],
+        ]);
+        let bosa_reader = BosaReader::new(file.path().to_str().unwrap());
+        assert_eq!(bosa_reader.unwrap_err(), BosaError::MultipleKbo);
+    }
+
+    #[test]
+    fn test_bosa_reader_new_formatted_kbo() {
+        let file = bosa_file(&[
+            ["0208.322.049", "80080703041", "202310", "1", "495", "2", "811,48"],
+            ["208322049", "80080703041", "202311", "1", "495", "2", "1.622,95"],
+        ]);
+        let bosa_reader = BosaReader::new(file.path().to_str().unwrap()).unwrap();
+        assert_eq!(bosa_reader.kbo_nummer.to_string(), "0208.322.049");
+    }
+
+    #[test]
+    fn test_bosa_reader_new_invalid_kbo() {
+        let file = bosa_file(&[
+            ["208322048", "80080703041", "202310", "1", "495", "2", "811,48"],
+        ]);
+        let bosa_reader = BosaReader::new(file.path().to_str().unwrap());
+        assert_eq!(bosa_reader.unwrap_err(), BosaError::InvalidKbo);
+    }
+
+    #[test]
+    fn test_bosa_reader_new_kbo_not_found() {
+        let bosa_reader = BosaReader::new("tests/fixtures/test.xlsx");
+        assert_eq!(bosa_reader.unwrap_err(), BosaError::KboNotFound);
+    }
+
+    #[test]
+    fn test_bosa_reader_data() {
+        let file = bosa_file(&[
+            ["208322049", "80080703041", "202310", "1", "495", "2", "811,48"],
+            ["208322049", "80080703041", "202311", "1", "495", "2", "1.622,95"],
+            ["208322049", "80080703041", "202401", "1", "495", "1", "4.000,00"],
+        ]);
+        let data = BosaReader::new(file.path().to_str().unwrap()).unwrap().data().unwrap();
+
+        let rrn = Rrn::new("80080703041").unwrap();
+        let kwartaal = Kwartaal::new("20234".to_string()).unwrap();
+        let entries = &data[&rrn][&kwartaal];
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].wgc, 1);
+        assert_eq!(entries[0].wnk, 495);
+        assert_eq!(entries[0].lc, 2);
+        assert_eq!(entries[0].brutto_loon, Money::from_cents(81148));
+        assert_eq!(entries[1].brutto_loon, Money::from_cents(162295));
+        assert_eq!(data[&rrn].len(), 2);
+    }
+
+    #[test]
+    fn test_bosa_reader_data_invalid_month() {
+        let file = bosa_file(&[
+            ["208322049", "80080703041", "202313", "1", "495", "2", "811,48"],
+        ]);
+        let result = BosaReader::new(file.path().to_str().unwrap()).unwrap().data();
+        match result.unwrap_err() {
+            BosaError::InvalidCell(fout) => {
+                assert_eq!(fout.cell(), "C2");
+                assert_eq!(fout.value, "202313");
+                assert_eq!(fout.expected, Kolomtype::Maand);
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_bosa_reader_data_lenient() {
+        let file = bosa_file(&[
+            ["208322049", "80080703041", "202313", "1", "495", "2", "811,48"],
+            ["208322049", "80080703041", "202301", "1", "x", "2", "veel"],
+            ["208322049", "80080703041", "202301", "1", "495", "2", "162,95"],
+            // A wrong control number is not a person
+            ["208322049", "80080703042", "202301", "1", "495", "2", "162,95"],
+        ]);
+        let (data, errors) = BosaReader::new(file.path().to_str().unwrap()).unwrap().data_lenient().unwrap();
+        let cells: Vec<String> = errors.iter().map(|error| error.cell()).collect();
+        assert_eq!(cells, ["C2", "E3", "G3", "B5"]);
+        assert_eq!(errors[2].expected, Kolomtype::Bedrag);
+        assert_eq!((errors[3].expected, errors[3].value.as_str()), (Kolomtype::Insz, "80080703042"));
+        let rrn = Rrn::new("80080703041").unwrap();
+        assert_eq!(data[&rrn].values().map(Vec::len).sum::<usize>(), 1);
+    }
+}
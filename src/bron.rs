@@ -0,0 +1,269 @@
+// Detection : what a file really is (from its content, not its name) and which source a workbook comes
+// from (from its sheets and header rows), so the user does not have to say which document is which.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use thiserror::Error;
+use zip::ZipArchive;
+use crate::schema::DMFA;
+use crate::xlsx::{Row, XlsxReader};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum BronError {
+    #[error("File not found.")]
+    FileNotFound,
+    #[error("Invalid file type {0}, expected xlsx.")]
+    InvalidFileType(FileType),
+    #[error("Invalid xlsx file: {0}")]
+    InvalidXlsx(String),
+    #[error("Not a DMFA, CIPAL, BOSA or verschil document.")]
+    Unknown,
+}
+
+/// The type of a file, from its content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileType {
+    /// An Office Open XML workbook.
+    Xlsx,
+    /// A binary (BIFF) Excel workbook.
+    Xls,
+    /// Delimited text.
+    Csv,
+    /// A zip archive that is not a workbook.
+    Zip,
+    /// Anything else, with its MIME type if it is known.
+    Other(String),
+}
+
+impl fmt::Display for FileType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            FileType::Xlsx => "xlsx",
+            FileType::Xls => "xls",
+            FileType::Csv => "csv",
+            FileType::Zip => "zip",
+            FileType::Other(mime) => mime,
+        })
+    }
+}
+
+/// The source of a workbook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Bron {
+    Dmfa,
+    Cipal,
+    Bosa,
+    /// A verschil document, as written by `VerschilReport` or by the accountants.
+    Verschil,
+}
+
+impl fmt::Display for Bron {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Bron::Dmfa => "DMFA",
+            Bron::Cipal => "CIPAL",
+            Bron::Bosa => "BOSA",
+            Bron::Verschil => "verschil",
+        })
+    }
+}
+
+/// Determines the type of a file from its first bytes, whatever its extension.
+///
+/// A zip archive is only an xlsx workbook if it holds `xl/workbook.xml`, and text is only csv if its first
+/// line has a separator (`,`, `;` or a tab).
+///
+/// # Errors
+///
+/// Returns `BronError::FileNotFound` if the file can not be read.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+/// use lvgl::{file_type, FileType};
+///
+/// assert_eq!(file_type(Path::new("tests/fixtures/207527540-cipal.xlsx")).unwrap(), FileType::Xlsx);
+/// assert_eq!(file_type(Path::new("Cargo.toml")).unwrap(), FileType::Other("text/plain".to_string()));
+/// ```
+pub fn file_type(path: &Path) -> Result<FileType, BronError> {
+    let mut head = Vec::with_capacity(8192);
+    File::open(path)
+        .and_then(|file| file.take(8192).read_to_end(&mut head))
+        .map_err(|_| BronError::FileNotFound)?;
+
+    match infer::get(&head).map(|kind| kind.mime_type()) {
+        Some("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet") => Ok(FileType::Xlsx),
+        // Not every writer puts the workbook parts first, for infer it is then a plain zip archive
+        Some("application/zip") => Ok(if is_workbook(path) { FileType::Xlsx } else { FileType::Zip }),
+        // Old Excel workbooks without the class id of Excel are only recognised as OLE compound files
+        Some("application/vnd.ms-excel" | "application/x-ole-storage") => Ok(FileType::Xls),
+        Some(mime) => Ok(FileType::Other(mime.to_string())),
+        None => match std::str::from_utf8(&head) {
+            // The block may end in the middle of a multi-byte character
+            Err(e) if e.error_len().is_some() => Ok(FileType::Other("application/octet-stream".to_string())),
+            _ => {
+                let text = String::from_utf8_lossy(&head);
+                let first_line = text.lines().next().unwrap_or_default();
+                if first_line.contains([',', ';', '\t']) {
+                    Ok(FileType::Csv)
+                } else {
+                    Ok(FileType::Other("text/plain".to_string()))
+                }
+            }
+        },
+    }
+}
+
+/// Checks that the file is an xlsx workbook. The extension is not enough : a csv or an old xls workbook is
+/// often just renamed.
+///
+/// # Errors
+///
+/// Returns `BronError::FileNotFound` if the file can not be read, and `BronError::InvalidFileType` if it is
+/// not an xlsx workbook.
+pub(crate) fn require_xlsx(path: &Path) -> Result<(), BronError> {
+    match file_type(path)? {
+        FileType::Xlsx => Ok(()),
+        other => Err(BronError::InvalidFileType(other)),
+    }
+}
+
+fn is_workbook(path: &Path) -> bool {
+    File::open(path)
+        .ok()
+        .and_then(|file| ZipArchive::new(BufReader::new(file)).ok())
+        .is_some_and(|mut archive| archive.by_name("xl/workbook.xml").is_ok())
+}
+
+/// Classifies a workbook as DMFA, CIPAL, BOSA or verschil document from its header signature.
+///
+/// * CIPAL : an "Info" sheet, and a "Data" sheet with the naam, voornaam and insznr columns.
+/// * DMFA : two header rows naming the Kwart, RSZnr and KBO columns, in Dutch (Kwart, RSZnr, KBO) or French
+///   (Trim, NOSS, BCE).
+/// * BOSA : one header row with the KBO, INSZ, Periode, Looncode and Bedrag columns.
+/// * verschil : a header row starting with Kwart, with the WGC, WNK, INSZ and Verschil columns.
+///
+/// Only the first rows of each sheet are read.
+///
+/// # Errors
+///
+/// Returns `BronError::InvalidFileType` if the file is not an xlsx workbook, whatever its extension, and
+/// `BronError::Unknown` if no signature matches.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+/// use lvgl::{detect, Bron};
+///
+/// assert_eq!(detect(Path::new("tests/fixtures/207527540-dmfa.xlsx")).unwrap(), Bron::Dmfa);
+/// assert_eq!(detect(Path::new("tests/fixtures/207527540-cipal.xlsx")).unwrap(), Bron::Cipal);
+/// ```
+pub fn detect(path: &Path) -> Result<Bron, BronError> {
+    require_xlsx(path)?;
+
+    let mut workbook = XlsxReader::open(path).map_err(|e| BronError::InvalidXlsx(e.to_string()))?;
+    let sheet_names: Vec<String> = workbook.sheet_names().iter().map(|name| name.to_string()).collect();
+
+    if sheet_names.iter().any(|name| name == "Info") {
+        if let Ok(mut rows) = workbook.sheet_by_name("Data") {
+            if let Some(Ok(header)) = rows.next() {
+                if has_columns(&header, &["naam", "voornaam", "insznr"]) {
+                    return Ok(Bron::Cipal);
+                }
+            }
+        }
+    }
+
+    for index in 0..sheet_names.len() {
+        let rows: Vec<Row> = workbook.sheet(index)
+            .map_err(|e| BronError::InvalidXlsx(e.to_string()))?
+            .take(10)
+            .collect::<Result<_, _>>()
+            .map_err(|e| BronError::InvalidXlsx(e.to_string()))?;
+
+        let header = |number: u32| rows.iter().find(|row| row.number == number);
+        let columns = DMFA.zoek(header(1).into_iter().chain(header(2)));
+        if header(2).is_some() && columns.has(&["Kwart", "RSZnr", "KBO"]) {
+            return Ok(Bron::Dmfa);
+        }
+        if header(1).is_some_and(|row| has_columns(row, &["kbo", "insz", "periode", "looncode", "bedrag"])) {
+            return Ok(Bron::Bosa);
+        }
+        let verschil = rows.iter().any(|row| {
+            row.get(1).trim().to_lowercase().starts_with("kwart")
+                && has_columns(row, &["wgc", "wnk", "insz"])
+                && row.cells().any(|(_, value)| value.trim().to_lowercase().starts_with("verschil"))
+        });
+        if verschil {
+            return Ok(Bron::Verschil);
+        }
+    }
+
+    Err(BronError::Unknown)
+}
+
+/// Whether the row has all the columns, compared case insensitive.
+fn has_columns(row: &Row, columns: &[&str]) -> bool {
+    let names: Vec<String> = row.cells().map(|(_, value)| value.trim().to_lowercase()).collect();
+    columns.iter().all(|column| names.iter().any(|name| name == column))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::Builder;
+    use crate::test_util::bosa_file;
+
+    #[test]
+    fn test_file_type() {
+        assert_eq!(file_type(Path::new("tests/fixtures/207527540-dmfa.xlsx")), Ok(FileType::Xlsx));
+        assert_eq!(file_type(Path::new("tests/fixtures/207527540-cipal.xlsx")), Ok(FileType::Xlsx));
+        assert_eq!(file_type(Path::new("tests/fixtures/nonexistent.xlsx")), Err(BronError::FileNotFound));
+
+        // The extension does not matter
+        let csv = Builder::new().suffix(".xlsx").tempfile().unwrap();
+        fs::write(csv.path(), "insz;bedrag\n69100136359;12,50\n").unwrap();
+        assert_eq!(file_type(csv.path()), Ok(FileType::Csv));
+
+        let zip = Builder::new().suffix(".xlsx").tempfile().unwrap();
+        let mut writer = zip::ZipWriter::new(fs::File::create(zip.path()).unwrap());
+        writer.start_file("data.txt", zip::write::SimpleFileOptions::default()).unwrap();
+        writer.finish().unwrap();
+        assert_eq!(file_type(zip.path()), Ok(FileType::Zip));
+
+        let xls = Builder::new().suffix(".xlsx").tempfile().unwrap();
+        let mut ole = vec![0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+        ole.resize(1024, 0);
+        fs::write(xls.path(), ole).unwrap();
+        assert_eq!(file_type(xls.path()), Ok(FileType::Xls));
+    }
+
+    #[test]
+    fn test_require_xlsx() {
+        assert_eq!(require_xlsx(Path::new("tests/fixtures/207527540-dmfa.xlsx")), Ok(()));
+        assert_eq!(require_xlsx(Path::new("tests/fixtures/nonexistent.xlsx")), Err(BronError::FileNotFound));
+        assert_eq!(require_xlsx(Path::new("Cargo.toml")), Err(BronError::InvalidFileType(FileType::Other("text/plain".to_string()))));
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(detect(Path::new("tests/fixtures/207527540-dmfa.xlsx")), Ok(Bron::Dmfa));
+        assert_eq!(detect(Path::new("tests/fixtures/208322049-dmfa.xlsx")), Ok(Bron::Dmfa));
+        assert_eq!(detect(Path::new("tests/fixtures/207527540-cipal.xlsx")), Ok(Bron::Cipal));
+        assert_eq!(detect(Path::new("tests/fixtures/207527540-verschil.xlsx")), Ok(Bron::Verschil));
+        assert_eq!(detect(Path::new("tests/fixtures/208322049-verschil.xlsx")), Ok(Bron::Verschil));
+        assert_eq!(detect(Path::new("tests/fixtures/test.xlsx")), Err(BronError::Unknown));
+        assert_eq!(detect(Path::new("Cargo.toml")), Err(BronError::InvalidFileType(FileType::Other("text/plain".to_string()))));
+    }
+
+    #[test]
+    fn test_detect_bosa() {
+        let file = bosa_file(&[]);
+        assert_eq!(detect(file.path()), Ok(Bron::Bosa));
+    }
+}
@@ -0,0 +1,484 @@
+// CIPAL = loonbestand van de lokale besturen (CIPAL Schaubroeck)
+// Rapport RP10088 : Overzicht van alle brutolooncomponenten
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use time::{Date, Duration, Month, PrimitiveDateTime, Time};
+use thiserror::Error;
+use crate::bron::{require_xlsx, BronError};
+use crate::diagnose::{CellError, RowReader};
+use crate::types::{CipalMonth, Money, Rrn};
+use crate::schema::{Kolomtype, SchemaError, CIPAL};
+use crate::xlsx::{Row, Rows, XlsxReader};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CipalError {
+    #[error("Invalid filename.")]
+    InvalidFilename,
+    #[error("File not found.")]
+    FileNotFound,
+    #[error("Invalid file type {0}, expected xlsx.")]
+    InvalidFileType(String),
+    #[error("Invalid xlsx file: {0}")]
+    InvalidXlsx(String),
+    #[error("Sheet {0} not found.")]
+    SheetNotFound(String),
+    #[error("Column {0} not found.")]
+    ColumnNotFound(String),
+    #[error("Info field {0} not found.")]
+    InfoNotFound(String),
+    #[error("Invalid info field {0}.")]
+    InvalidInfo(String),
+    #[error("{0}")]
+    InvalidCell(CellError),
+    #[error("Expected {expected} rows, found {found}.")]
+    RowCountMismatch { expected: u32, found: u32 },
+}
+
+/// One brutolooncomponent of one person in one month.
+#[derive(Debug, PartialEq)]
+pub struct CipalEntry {
+    pub naam: String,
+    pub voornaam: String,
+    pub insz: Rrn,                  // insznr
+    pub persnr: u32,                // Personeelsnummer
+    pub maand: CipalMonth,          // maand/jaar
+    pub wgc: u16,                   // wgcategorie
+    pub wnk: u16,                   // wnkengetal
+    pub aard: u16,                  // Aard van de looncode (vergoeding, supplementen, ...)
+    pub code: u16,                  // CIPAL looncode
+    pub extensie: u16,              // Extensie van de CIPAL looncode
+    pub bezoldigingscode: u16,      // DMFA bezoldigingscode
+    pub omschrijving: String,       // korte omschrijving looncode
+    pub kostenplaats: u32,
+    pub brutobedrag: Money,
+    pub patr_rsz: Money,            // Patronale RSZ bijdrage
+    pub patr_pens: Money,           // Patronale pensioenbijdrage
+}
+
+/// The metadata of the "Info" sheet of a CIPAL report.
+#[derive(Debug, PartialEq)]
+pub struct CipalInfo {
+    pub databron: String,
+    pub databron_id: String,
+    pub aangemaakt_op: PrimitiveDateTime,
+    pub rapport: String,
+    pub rapport_id: String,
+    pub aantal_rijen: u32,
+    pub gebruiker: String,
+    pub begindatum: Date,
+    pub einddatum: Date,
+    pub controle_datamart: Option<PrimitiveDateTime>,
+    pub creatie_datamart: Option<PrimitiveDateTime>,
+    pub laatste_creatie_betalingen: Option<PrimitiveDateTime>,
+    pub laatste_loonberekening: Option<PrimitiveDateTime>,
+}
+
+#[derive(Debug)]
+pub struct CipalReader {
+    pub path: PathBuf,
+    pub info: CipalInfo,
+}
+
+impl CipalReader {
+    /// Creates a new `CipalReader` instance and reads the "Info" sheet.
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - A string slice that holds the filename of the CIPAL file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CipalError` if the filename is invalid, the file is not found, the file is not an xlsx workbook
+    /// whatever its extension, the "Data" or "Info" sheet is missing, or the "Info" sheet can not be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lvgl::CipalReader;
+    ///
+    /// let cipal_reader = CipalReader::new("tests/fixtures/207527540-cipal.xlsx").unwrap();
+    /// assert_eq!(cipal_reader.info.rapport_id, "RP10088");
+    /// ```
+    pub fn new(filename: &str) -> Result<Self, CipalError> {
+        if filename.is_empty() {
+            return Err(CipalError::InvalidFilename);
+        }
+
+        let path = Path::new(filename).to_path_buf();
+
+        if !path.exists() {
+            return Err(CipalError::FileNotFound);
+        }
+
+        require_xlsx(&path).map_err(|e| match e {
+            BronError::InvalidFileType(soort) => CipalError::InvalidFileType(soort.to_string()),
+            _ => CipalError::FileNotFound,
+        })?;
+
+        let mut workbook = XlsxReader::open(&path).map_err(|e| CipalError::InvalidXlsx(e.to_string()))?;
+        if !workbook.sheet_names().contains(&"Data") {
+            return Err(CipalError::SheetNotFound("Data".to_string()));
+        }
+        let rows = workbook.sheet_by_name("Info").map_err(|_| CipalError::SheetNotFound("Info".to_string()))?;
+        let info = read_info(rows)?;
+
+        Ok(CipalReader { path, info })
+    }
+
+    /// Reads all brutolooncomponenten from the "Data" sheet.
+    ///
+    /// The first row is the header and is skipped. The month is read from the "maand/jaar" column, or from
+    /// the "jaar" and "maand" columns if there is none.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CipalError::ColumnNotFound` with all the required columns missing from the header,
+    /// `CipalError::InvalidCell` with the place, value and expected type of the first cell that can not be
+    /// parsed, and `CipalError::RowCountMismatch` if the number of lines does not match the "Aantal rijen" of
+    /// the "Info" sheet.
+    pub fn data(&self) -> Result<Vec<CipalEntry>, CipalError> {
+        self.read(false).map(|(data, _)| data)
+    }
+
+    /// Reads all brutolooncomponenten like `data`, but skips the lines with cells that can not be parsed and
+    /// returns these cells. The skipped lines still count for the "Aantal rijen" of the "Info" sheet.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CipalError::ColumnNotFound` with all the required columns missing from the header, and
+    /// `CipalError::RowCountMismatch` if the number of lines does not match the "Aantal rijen".
+    pub fn data_lenient(&self) -> Result<(Vec<CipalEntry>, Vec<CellError>), CipalError> {
+        self.read(true)
+    }
+
+    fn read(&self, lenient: bool) -> Result<(Vec<CipalEntry>, Vec<CellError>), CipalError> {
+        let mut workbook = XlsxReader::open(&self.path).map_err(|e| CipalError::InvalidXlsx(e.to_string()))?;
+        let mut rows = workbook.sheet_by_name("Data").map_err(|_| CipalError::SheetNotFound("Data".to_string()))?;
+
+        let header: Vec<Row> = rows.by_ref().take(1).collect::<Result<_, _>>().map_err(|e| CipalError::InvalidXlsx(e.to_string()))?;
+        let kolommen = CIPAL.kolommen(&header).map_err(|e| match e {
+            SchemaError::ColumnsNotFound(namen) => CipalError::ColumnNotFound(namen.join(", ")),
+            _ => CipalError::FileNotFound,
+        })?;
+        let column = |name: &str| {
+            kolommen.get(name).ok_or_else(|| CipalError::ColumnNotFound(name.to_string()))
+        };
+
+        let naam_col = column("naam")?;
+        let voornaam_col = column("voornaam")?;
+        let insz_col = column("insznr")?;
+        let persnr_col = column("persnr")?;
+        let maand_kolom = match (kolommen.get("maand/jaar"), kolommen.get("jaar"), kolommen.get("maand")) {
+            (Some(maand_jaar), _, _) => MaandKolom::MaandJaar(maand_jaar),
+            (None, Some(jaar), Some(maand)) => MaandKolom::JaarMaand(jaar, maand),
+            _ => return Err(CipalError::ColumnNotFound("maand/jaar".to_string())),
+        };
+        let wgc_col = column("wgcategorie")?;
+        let wnk_col = column("wnkengetal")?;
+        let aard_col = column("aard")?;
+        let code_col = column("code")?;
+        let extensie_col = column("extensie")?;
+        let bezoldigingscode_col = column("bezoldigingscode")?;
+        let omschrijving_col = column("korte omschrijving looncode")?;
+        let kostenplaats_col = column("kostenplaats")?;
+        let brutobedrag_col = column("brutobedrag")?;
+        let patr_rsz_col = column("patr.rsz")?;
+        let patr_pens_col = column("patr.pens")?;
+
+        let mut data = Vec::new();
+        let mut errors = Vec::new();
+        let mut overgeslagen = 0;
+
+        for row in rows {
+            let row = row.map_err(|e| CipalError::InvalidXlsx(e.to_string()))?;
+            if row.get(insz_col).trim().is_empty() {
+                continue;
+            }
+
+            let mut cells = RowReader::new(&row, &self.path, "Data");
+            let insz = cells.read(insz_col, "insznr", Kolomtype::Insz, |waarde| Rrn::new_checked(waarde).ok());
+            let persnr = cells.read(persnr_col, "persnr", Kolomtype::Geheel, getal);
+            let maand = match maand_kolom {
+                MaandKolom::MaandJaar(column) => cells.read(column, "maand/jaar", Kolomtype::Maand, |waarde| {
+                    CipalMonth::new(waarde.to_string()).ok()
+                }),
+                MaandKolom::JaarMaand(jaar, maand) => cells.read(maand, "maand", Kolomtype::Maand, |waarde| {
+                    CipalMonth::from_jaar_maand(row.get(jaar), waarde).ok()
+                }),
+            };
+            let wgc = cells.read(wgc_col, "wgcategorie", Kolomtype::Geheel, getal);
+            let wnk = cells.read(wnk_col, "wnkengetal", Kolomtype::Geheel, getal);
+            let aard = cells.read(aard_col, "aard", Kolomtype::Geheel, getal);
+            let code = cells.read(code_col, "code", Kolomtype::Geheel, getal);
+            let extensie = cells.read(extensie_col, "extensie", Kolomtype::Geheel, getal);
+            let bezoldigingscode = cells.read(bezoldigingscode_col, "bezoldigingscode", Kolomtype::Geheel, getal);
+            let kostenplaats = cells.read(kostenplaats_col, "kostenplaats", Kolomtype::Geheel, getal);
+            let brutobedrag = cells.read(brutobedrag_col, "brutobedrag", Kolomtype::Bedrag, getal);
+            let patr_rsz = cells.read(patr_rsz_col, "patr.rsz", Kolomtype::Bedrag, getal);
+            let patr_pens = cells.read(patr_pens_col, "patr.pens", Kolomtype::Bedrag, getal);
+            cells.finish(lenient, &mut errors).map_err(CipalError::InvalidCell)?;
+
+            let entry = (|| Some(CipalEntry {
+                naam: row.get(naam_col).trim().to_string(),
+                voornaam: row.get(voornaam_col).trim().to_string(),
+                insz: insz?,
+                persnr: persnr?,
+                maand: maand?,
+                wgc: wgc?,
+                wnk: wnk?,
+                aard: aard?,
+                code: code?,
+                extensie: extensie?,
+                bezoldigingscode: bezoldigingscode?,
+                omschrijving: row.get(omschrijving_col).trim().to_string(),
+                kostenplaats: kostenplaats?,
+                brutobedrag: brutobedrag?,
+                patr_rsz: patr_rsz?,
+                patr_pens: patr_pens?,
+            }))();
+            match entry {
+                Some(entry) => data.push(entry),
+                None => overgeslagen += 1,
+            }
+        }
+
+        let found = data.len() as u32 + overgeslagen;
+        if found != self.info.aantal_rijen {
+            return Err(CipalError::RowCountMismatch { expected: self.info.aantal_rijen, found });
+        }
+
+        Ok((data, errors))
+    }
+}
+
+fn read_info(rows: Rows) -> Result<CipalInfo, CipalError> {
+    // The labels are in the first column, the values in the second column
+    let mut fields: HashMap<String, String> = HashMap::new();
+    for row in rows {
+        let row = row.map_err(|e| CipalError::InvalidXlsx(e.to_string()))?;
+        let label = row.get(1).trim().trim_end_matches(':');
+        if !label.is_empty() {
+            fields.insert(label.to_string(), row.get(2).trim().to_string());
+        }
+    }
+
+    let field = |name: &str| {
+        fields.get(name).cloned().ok_or_else(|| CipalError::InfoNotFound(name.to_string()))
+    };
+    let invalid = |name: &str| CipalError::InvalidInfo(name.to_string());
+    let timestamp = |name: &str| match fields.get(name) {
+        Some(value) => parse_datetime(value).map(Some).ok_or_else(|| invalid(name)),
+        None => Ok(None),
+    };
+
+    let aangemaakt_op = field("Aangemaakt op")?;
+
+    Ok(CipalInfo {
+        databron: field("Databron")?,
+        databron_id: field("Databron ID")?,
+        aangemaakt_op: aangemaakt_op.parse::<f64>().ok()
+            .and_then(excel_datetime)
+            .or_else(|| parse_datetime(&aangemaakt_op))
+            .ok_or_else(|| invalid("Aangemaakt op"))?,
+        rapport: field("Rapport")?,
+        rapport_id: field("Rapport ID")?,
+        aantal_rijen: field("Aantal rijen")?.parse().map_err(|_| invalid("Aantal rijen"))?,
+        gebruiker: field("Gebruiker").unwrap_or_default(),
+        begindatum: parse_date(&field("begindatum")?).ok_or_else(|| invalid("begindatum"))?,
+        einddatum: parse_date(&field("einddatum")?).ok_or_else(|| invalid("einddatum"))?,
+        controle_datamart: timestamp("Controle datum voor datamart")?,
+        creatie_datamart: timestamp("Creatie van de datamart")?,
+        laatste_creatie_betalingen: timestamp("Laatste creatie betalingen")?,
+        laatste_loonberekening: timestamp("Laatste geregistreerde loonberekening")?,
+    })
+}
+
+/// The column(s) holding the month of a line.
+#[derive(Clone, Copy)]
+enum MaandKolom {
+    /// "maand/jaar", e.g. `1/2023`.
+    MaandJaar(u32),
+    /// "jaar" and "maand", e.g. `2023.0` and `1.0`.
+    JaarMaand(u32, u32),
+}
+
+/// Parses a numeric cell, CIPAL exports codes as floating point numbers (e.g. `751.0`).
+fn getal<T: FromStr>(value: &str) -> Option<T> {
+    let value = value.trim();
+    value.strip_suffix(".0").unwrap_or(value).parse().ok()
+}
+
+/// Parses a `dd/mm/yyyy` date.
+fn parse_date(value: &str) -> Option<Date> {
+    let mut parts = value.trim().split('/');
+    let day = parts.next()?.parse::<u8>().ok()?;
+    let month = Month::try_from(parts.next()?.parse::<u8>().ok()?).ok()?;
+    let year = parts.next()?.parse::<i32>().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Date::from_calendar_date(year, month, day).ok()
+}
+
+/// Parses a `dd/mm/yyyy HH:MM:SS` timestamp.
+fn parse_datetime(value: &str) -> Option<PrimitiveDateTime> {
+    let (date, time) = value.trim().split_once(' ')?;
+    let mut parts = time.split(':').map(|part| part.parse::<u8>().ok());
+    let hour = parts.next()??;
+    let minute = parts.next()??;
+    let second = parts.next().unwrap_or(Some(0))?;
+    let time = Time::from_hms(hour, minute, second).ok()?;
+    Some(PrimitiveDateTime::new(parse_date(date)?, time))
+}
+
+/// Converts an Excel serial date (days since 1899-12-30) to a timestamp, rounded to the second.
+fn excel_datetime(serial: f64) -> Option<PrimitiveDateTime> {
+    if !serial.is_finite() || serial < 0.0 {
+        return None;
+    }
+    let epoch = Date::from_calendar_date(1899, Month::December, 30).ok()?;
+    let seconds = (serial * 86400.0).round() as i64;
+    PrimitiveDateTime::new(epoch, Time::MIDNIGHT).checked_add(Duration::seconds(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use umya_spreadsheet::reader::xlsx;
+    use crate::test_util::broken_xlsx;
+
+    #[test]
+    fn test_cipal_reader_new_valid() {
+        let cipal_reader = CipalReader::new("tests/fixtures/207527540-cipal.xlsx").unwrap();
+        let info = &cipal_reader.info;
+        assert_eq!(info.databron, "Zoutleeuw");
+        assert_eq!(info.databron_id, "0657.prod");
+        assert_eq!(info.rapport_id, "RP10088");
+        assert_eq!(info.aantal_rijen, 2534);
+        assert_eq!(info.begindatum, Date::from_calendar_date(2023, Month::January, 1).unwrap());
+        assert_eq!(info.einddatum, Date::from_calendar_date(2023, Month::December, 31).unwrap());
+        assert_eq!(info.aangemaakt_op.date(), Date::from_calendar_date(2024, Month::October, 21).unwrap());
+        assert_eq!(
+            info.creatie_datamart,
+            Some(PrimitiveDateTime::new(
+                Date::from_calendar_date(2024, Month::October, 19).unwrap(),
+                Time::from_hms(1, 59, 38).unwrap(),
+            ))
+        );
+    }
+
+    #[test]
+    fn test_cipal_reader_new_invalid_filename() {
+        let cipal_reader = CipalReader::new("");
+        assert_eq!(cipal_reader.unwrap_err(), CipalError::InvalidFilename);
+    }
+
+    #[test]
+    fn test_cipal_reader_new_other_extension() {
+        // The content decides, not the extension
+        let file = tempfile::Builder::new().suffix(".dat").tempfile().unwrap();
+        std::fs::copy("tests/fixtures/207527540-cipal.xlsx", file.path()).unwrap();
+        assert!(CipalReader::new(file.path().to_str().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_cipal_reader_new_file_not_found() {
+        let cipal_reader = CipalReader::new("tests/fixtures/nonexistent.xlsx");
+        assert_eq!(cipal_reader.unwrap_err(), CipalError::FileNotFound);
+    }
+
+    #[test]
+    fn test_cipal_reader_new_invalid_xlsx() {
+        let file = broken_xlsx();
+        let cipal_reader = CipalReader::new(file.path().to_str().unwrap());
+        let expected = "Invalid xlsx file: xl/_rels/workbook.xml.rels not found";
+        assert_eq!(cipal_reader.unwrap_err(), CipalError::InvalidXlsx(expected.to_string()));
+    }
+
+    #[test]
+    fn test_cipal_reader_new_no_data_sheet() {
+        let cipal_reader = CipalReader::new("tests/fixtures/test.xlsx");
+        assert_eq!(cipal_reader.unwrap_err(), CipalError::SheetNotFound("Data".to_string()));
+    }
+
+    #[test]
+    fn test_cipal_reader_data() {
+        let cipal_reader = CipalReader::new("tests/fixtures/207527540-cipal.xlsx").unwrap();
+        let data = cipal_reader.data().unwrap();
+        assert_eq!(data.len(), 2534);
+
+        let entry = &data[2];
+        assert_eq!(entry.naam, "AMTER");
+        assert_eq!(entry.insz, Rrn::new("910524-313-40").unwrap());
+        assert_eq!(entry.persnr, 576);
+        assert_eq!(entry.maand, CipalMonth::new("1/2023".to_string()).unwrap());
+        assert_eq!(entry.wgc, 751);
+        assert_eq!(entry.wnk, 675);
+        assert_eq!(entry.aard, 11);
+        assert_eq!(entry.code, 101);
+        assert_eq!(entry.bezoldigingscode, 1);
+        assert_eq!(entry.omschrijving, "LOON");
+        assert_eq!(entry.brutobedrag, Money::from_cents(334983));
+        assert_eq!(entry.patr_rsz, Money::from_cents(51855));
+        assert_eq!(entry.patr_pens, Money::from_cents(112219));
+    }
+
+    #[test]
+    fn test_cipal_reader_data_jaar_maand() {
+        // Without a "maand/jaar" column the month comes from the "jaar" and "maand" columns
+        let mut book = xlsx::read("tests/fixtures/207527540-cipal.xlsx").unwrap();
+        let sheet = book.get_sheet_by_name_mut("Data").unwrap();
+        let column = (1..=sheet.get_highest_column())
+            .find(|column| sheet.get_value((*column, 1)).trim() == "maand/jaar")
+            .unwrap();
+        sheet.get_cell_mut((column, 1)).set_value("periode");
+        let file = tempfile::Builder::new().suffix(".xlsx").tempfile().unwrap();
+        umya_spreadsheet::writer::xlsx::write(&book, file.path()).unwrap();
+
+        let data = CipalReader::new(file.path().to_str().unwrap()).unwrap().data().unwrap();
+        assert_eq!(data.len(), 2534);
+        assert_eq!(data[2].maand, CipalMonth::new("1/2023".to_string()).unwrap());
+    }
+
+    #[test]
+    fn test_cipal_reader_data_lenient() {
+        let mut book = xlsx::read("tests/fixtures/207527540-cipal.xlsx").unwrap();
+        let sheet = book.get_sheet_by_name_mut("Data").unwrap();
+        let column = (1..=sheet.get_highest_column())
+            .find(|column| sheet.get_value((*column, 1)).trim() == "brutobedrag")
+            .unwrap();
+        sheet.get_cell_mut((column, 4)).set_value("n.v.t.");
+        let file = tempfile::Builder::new().suffix(".xlsx").tempfile().unwrap();
+        umya_spreadsheet::writer::xlsx::write(&book, file.path()).unwrap();
+
+        let cipal_reader = CipalReader::new(file.path().to_str().unwrap()).unwrap();
+        let error = match cipal_reader.data().unwrap_err() {
+            CipalError::InvalidCell(error) => error,
+            e => panic!("unexpected error {:?}", e),
+        };
+        assert_eq!((error.sheet.as_str(), error.column, error.row), ("Data", column, 4));
+        assert_eq!(error.value, "n.v.t.");
+        assert_eq!(error.expected, Kolomtype::Bedrag);
+
+        // The skipped line still counts for the "Aantal rijen"
+        let (data, errors) = cipal_reader.data_lenient().unwrap();
+        assert_eq!(data.len(), 2533);
+        assert_eq!(errors, [error]);
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("31/12/2023"), Date::from_calendar_date(2023, Month::December, 31).ok());
+        assert_eq!(parse_date("31/02/2023"), None);
+        assert_eq!(parse_date("2023-12-31"), None);
+    }
+
+    #[test]
+    fn test_excel_datetime() {
+        let datetime = excel_datetime(45586.38960042824).unwrap();
+        assert_eq!(datetime.date(), Date::from_calendar_date(2024, Month::October, 21).unwrap());
+        assert_eq!(datetime.time(), Time::from_hms(9, 21, 1).unwrap());
+    }
+}
@@ -0,0 +1,277 @@
+// Codes of the DmfAPPL : what a werkgeverscategorie (WGC), werknemerskengetal (WNK) or looncode (LC) means.
+// The catalogue is loaded from TOML, the embedded default is an excerpt with the codes of the documents reconciled
+// so far and no validity periods, not the full lists of the RSZ.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use serde::Deserialize;
+use thiserror::Error;
+use crate::compare::LoonKey;
+use crate::types::Kwartaal;
+
+/// The default catalogue.
+pub const DEFAULT_CODES: &str = include_str!("../config/codes.toml");
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CodesError {
+    #[error("Invalid filename.")]
+    InvalidFilename,
+    #[error("File not found.")]
+    FileNotFound,
+    #[error("Invalid file extension.")]
+    InvalidExtension,
+    #[error("Invalid TOML codes : {0}")]
+    Toml(String),
+    #[error("Invalid period {0}, expected YYYYQ.")]
+    InvalidPeriod(u32),
+}
+
+/// The kind of a code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Codesoort {
+    Wgc,
+    Wnk,
+    Lc,
+}
+
+impl fmt::Display for Codesoort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Codesoort::Wgc => "werkgeverscategorie",
+            Codesoort::Wnk => "werknemerskengetal",
+            Codesoort::Lc => "looncode",
+        })
+    }
+}
+
+/// One code of the catalogue, a code may have several entries for different periods.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Code {
+    pub code: u16,
+    pub nl: String,             // Nederlandse omschrijving
+    pub fr: String,             // Description française
+    pub van: Option<u32>,       // Eerste kwartaal (YYYYQ) waarin de code geldt
+    pub tot: Option<u32>,       // Laatste kwartaal (YYYYQ) waarin de code geldt
+}
+
+impl Code {
+    /// Checks if the code is valid in the quarter.
+    pub fn geldig(&self, kwart: &Kwartaal) -> bool {
+        let kwartaal = kwart.year as u32 * 10 + kwart.quarter as u32;
+        self.van.is_none_or(|van| kwartaal >= van) && self.tot.is_none_or(|tot| kwartaal <= tot)
+    }
+
+    fn validate(&self) -> Result<(), CodesError> {
+        for periode in self.van.iter().chain(self.tot.iter()) {
+            if !(19701..=21004).contains(periode) || !(1..=4).contains(&(periode % 10)) {
+                return Err(CodesError::InvalidPeriod(*periode));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A code of a wage line that is not in the catalogue, or not in the quarter of the line.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Codefout {
+    Onbekend { soort: Codesoort, code: u16 },
+    Ongeldig { soort: Codesoort, code: u16, kwart: Kwartaal },
+}
+
+impl fmt::Display for Codefout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Codefout::Onbekend { soort, code } => write!(f, "Unknown {} {}.", soort, code),
+            Codefout::Ongeldig { soort, code, kwart } => write!(f, "The {} {} is not valid in {}.", soort, code, kwart),
+        }
+    }
+}
+
+/// The catalogue of the werkgeverscategorieën, werknemerskengetallen and looncodes.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Codes {
+    #[serde(default, rename = "wgc")]
+    pub werkgeverscategorieen: Vec<Code>,
+    #[serde(default, rename = "wnk")]
+    pub werknemerskengetallen: Vec<Code>,
+    #[serde(default, rename = "lc")]
+    pub looncodes: Vec<Code>,
+}
+
+impl Codes {
+    /// The embedded default catalogue (config/codes.toml).
+    pub fn standaard() -> Self {
+        Codes::from_toml(DEFAULT_CODES).expect("the embedded catalogue is valid")
+    }
+
+    /// Loads a catalogue from a TOML file.
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - A string slice that holds the filename of the catalogue.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CodesError` if the filename is invalid, the file is not found, the extension is not .toml,
+    /// or the content is not a valid catalogue.
+    pub fn load(filename: &str) -> Result<Self, CodesError> {
+        if filename.is_empty() {
+            return Err(CodesError::InvalidFilename);
+        }
+
+        let extension = Path::new(filename).extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+        if extension.as_deref() != Some("toml") {
+            return Err(CodesError::InvalidExtension);
+        }
+
+        let content = fs::read_to_string(filename).map_err(|_| CodesError::FileNotFound)?;
+        Codes::from_toml(&content)
+    }
+
+    /// Parses a catalogue with `[[wgc]]`, `[[wnk]]` and `[[lc]]` tables.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lvgl::codes::{Codes, Codesoort};
+    /// use lvgl::Kwartaal;
+    ///
+    /// let codes = Codes::from_toml(r#"
+    ///     [[lc]]
+    ///     code = 1
+    ///     nl = "Bezoldiging"
+    ///     fr = "Rémunération"
+    ///     van = 20031
+    /// "#).unwrap();
+    /// let kwart = Kwartaal::new("20231".to_string()).unwrap();
+    /// assert_eq!(codes.omschrijving(Codesoort::Lc, 1, &kwart), Some("Bezoldiging"));
+    /// assert_eq!(codes.omschrijving(Codesoort::Lc, 1, &Kwartaal::new("20024".to_string()).unwrap()), None);
+    /// assert_eq!(codes.omschrijving(Codesoort::Wnk, 495, &kwart), None);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `CodesError::Toml` if the content can not be parsed, or `CodesError::InvalidPeriod`.
+    pub fn from_toml(content: &str) -> Result<Self, CodesError> {
+        let codes: Codes = toml::from_str(content).map_err(|e| CodesError::Toml(e.message().to_string()))?;
+        [Codesoort::Wgc, Codesoort::Wnk, Codesoort::Lc].iter()
+            .flat_map(|soort| codes.lijst(*soort))
+            .try_for_each(Code::validate)?;
+        Ok(codes)
+    }
+
+    /// The entries of one kind.
+    pub fn lijst(&self, soort: Codesoort) -> &[Code] {
+        match soort {
+            Codesoort::Wgc => &self.werkgeverscategorieen,
+            Codesoort::Wnk => &self.werknemerskengetallen,
+            Codesoort::Lc => &self.looncodes,
+        }
+    }
+
+    /// The entry of a code that is valid in the quarter.
+    pub fn zoek(&self, soort: Codesoort, code: u16, kwart: &Kwartaal) -> Option<&Code> {
+        self.lijst(soort).iter().find(|entry| entry.code == code && entry.geldig(kwart))
+    }
+
+    /// The Dutch description of the entry of a code that is valid in the quarter.
+    pub fn omschrijving(&self, soort: Codesoort, code: u16, kwart: &Kwartaal) -> Option<&str> {
+        self.zoek(soort, code, kwart).map(|entry| entry.nl.as_str())
+    }
+
+    /// Checks the WGC, WNK and LC of the lines : every code that is not in the catalogue, and every code that
+    /// is not valid in the quarter of a line, once and sorted.
+    pub fn controleer<'a>(&self, keys: impl IntoIterator<Item = &'a LoonKey>) -> Vec<Codefout> {
+        let mut fouten = BTreeSet::new();
+        for key in keys {
+            for (soort, code) in [(Codesoort::Wgc, key.wgc), (Codesoort::Wnk, key.wnk), (Codesoort::Lc, key.lc)] {
+                if !self.lijst(soort).iter().any(|entry| entry.code == code) {
+                    fouten.insert(Codefout::Onbekend { soort, code });
+                } else if self.zoek(soort, code, &key.kwart).is_none() {
+                    fouten.insert(Codefout::Ongeldig { soort, code, kwart: key.kwart.clone() });
+                }
+            }
+        }
+        fouten.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Rrn;
+
+    fn key(kwart: &str, wgc: u16, wnk: u16, lc: u16) -> LoonKey {
+        LoonKey {
+            kwart: Kwartaal::new(kwart.to_string()).unwrap(),
+            wgc,
+            wnk,
+            insz: Rrn::new("01032412205").unwrap(),
+            lc,
+        }
+    }
+
+    #[test]
+    fn test_codes_standaard() {
+        let codes = Codes::standaard();
+        let kwart = Kwartaal::new("20231".to_string()).unwrap();
+        assert_eq!(codes.omschrijving(Codesoort::Wnk, 405, &kwart), Some("Beschermde lokale mandatarissen"));
+        assert!(codes.omschrijving(Codesoort::Lc, 46, &kwart).unwrap().ends_with("aangegeven onder een andere code."));
+        assert!(codes.lijst(Codesoort::Lc).iter().all(|code| !code.fr.is_empty()));
+        assert_eq!(codes.omschrijving(Codesoort::Wnk, 840, &kwart), None);
+    }
+
+    #[test]
+    fn test_codes_geldig() {
+        let codes = Codes::from_toml(r#"
+            [[wnk]]
+            code = 495
+            nl = "Oud"
+            fr = "Ancien"
+            tot = 20224
+
+            [[wnk]]
+            code = 495
+            nl = "Nieuw"
+            fr = "Nouveau"
+            van = 20231
+        "#).unwrap();
+        let kwart = |kwart: &str| Kwartaal::new(kwart.to_string()).unwrap();
+        assert_eq!(codes.zoek(Codesoort::Wnk, 495, &kwart("20224")).unwrap().nl, "Oud");
+        assert_eq!(codes.zoek(Codesoort::Wnk, 495, &kwart("20231")).unwrap().fr, "Nouveau");
+        assert_eq!(codes.omschrijving(Codesoort::Wnk, 495, &kwart("20224")), Some("Oud"));
+        assert_eq!(codes.omschrijving(Codesoort::Wnk, 495, &kwart("20231")), Some("Nieuw"));
+
+        let invalid = Codes::from_toml("[[lc]]\ncode = 1\nnl = \"\"\nfr = \"\"\nvan = 20235\n");
+        assert_eq!(invalid.unwrap_err(), CodesError::InvalidPeriod(20235));
+    }
+
+    #[test]
+    fn test_codes_controleer() {
+        let mut codes = Codes::standaard();
+        codes.looncodes[0].van = Some(20232);
+        let keys = [key("20231", 751, 495, 1), key("20232", 751, 840, 1), key("20233", 751, 840, 999)];
+
+        let fouten = codes.controleer(&keys);
+        assert_eq!(fouten, [
+            Codefout::Onbekend { soort: Codesoort::Wnk, code: 840 },
+            Codefout::Onbekend { soort: Codesoort::Lc, code: 999 },
+            Codefout::Ongeldig { soort: Codesoort::Lc, code: 1, kwart: Kwartaal::new("20231".to_string()).unwrap() },
+        ]);
+        assert_eq!(fouten[0].to_string(), "Unknown werknemerskengetal 840.");
+        assert_eq!(fouten[2].to_string(), "The looncode 1 is not valid in 20231.");
+        assert_eq!(codes.controleer(&[]), []);
+    }
+
+    #[test]
+    fn test_codes_load() {
+        assert_eq!(Codes::load(""), Err(CodesError::InvalidFilename));
+        assert_eq!(Codes::load("codes.csv"), Err(CodesError::InvalidExtension));
+        assert_eq!(Codes::load("nonexistent.toml"), Err(CodesError::FileNotFound));
+        assert_eq!(Codes::load("config/codes.toml"), Ok(Codes::standaard()));
+    }
+}
@@ -0,0 +1,385 @@
+// Loonvergelijking : the wages of the loonbestand (CIPAL/BOSA) compared with the wages declared in the DMFA.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use time::Date;
+use thiserror::Error;
+use crate::bosa::{BosaEntry, BosaError, BosaReader};
+use crate::cipal::{CipalEntry, CipalError, CipalReader};
+use crate::dmfa::{DmfaEntry, DmfaError, DmfaReader};
+use crate::mapping::{Mapping, NietGemapt, Toewijzing};
+use crate::types::{Granularity, Kbo, Kwartaal, KwartaalError, KwartaalRange, Money, Period, PeriodError, Rrn};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CompareError {
+    #[error("Invalid path.")]
+    InvalidPath,
+    #[error("DMFA: {0}")]
+    Dmfa(#[from] DmfaError),
+    #[error("CIPAL: {0}")]
+    Cipal(#[from] CipalError),
+    #[error("BOSA: {0}")]
+    Bosa(#[from] BosaError),
+    #[error("KBO number of the DMFA ({dmfa}) does not match the KBO number of BOSA ({bosa}).")]
+    KboMismatch { dmfa: Kbo, bosa: Kbo },
+    #[error("CIPAL period: {0}")]
+    Kwartaal(#[from] KwartaalError),
+}
+
+/// The key on which the loonbestand and the DMFA are compared.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LoonKey {
+    pub kwart: Kwartaal,    // Kwartaal
+    pub wgc: u16,           // Werkgever cathegorie
+    pub wnk: u16,           // Werknemer kengetal
+    pub insz: Rrn,          // Rijksregister nummer
+    pub lc: u16,            // Looncode
+}
+
+/// The gross wage per `LoonKey`, summed over all lines.
+pub type LoonTotalen = HashMap<LoonKey, Money>;
+
+/// One line of the difference table : Kwart, WGC, WNK, INSZ, LC, Loonbestand, DMFA and Verschil.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Verschil {
+    pub key: LoonKey,
+    pub loonbestand: Money, // Brutoloon volgens het loonbestand
+    pub dmfa: Money,        // Brutoloon aangegeven in de DMFA
+}
+
+/// The result of a comparison with the CIPAL loonbestand : the differences, and the CIPAL codes that are
+/// not covered by the mapping (and therefore not compared).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vergelijking {
+    pub verschillen: Vec<Verschil>,
+    pub niet_gemapt: Vec<NietGemapt>,
+}
+
+impl Verschil {
+    /// The amount that still has to be declared (or corrected) in the DMFA.
+    pub fn verschil(&self) -> Money {
+        self.loonbestand - self.dmfa
+    }
+}
+
+/// Compares the DMFA with the CIPAL loonbestand.
+///
+/// The CIPAL bezoldigingscodes are translated to DMFA looncodes with the `mapping`. Only the quarters covered
+/// by the CIPAL report (begindatum - einddatum of the "Info" sheet) are compared, and only the lines with a
+/// difference are returned. The CIPAL codes the mapping does not know are returned as `niet_gemapt`.
+///
+/// # Errors
+///
+/// Returns `CompareError` if one of the files can not be read, and `CompareError::Kwartaal` if the begindatum of
+/// the CIPAL report comes after its einddatum.
+pub fn compare_dmfa_cipal(dmfa: &Path, cipal: &Path, mapping: &Mapping) -> Result<Vergelijking, CompareError> {
+    let dmfa_reader = DmfaReader::new(dmfa.to_str().ok_or(CompareError::InvalidPath)?)?;
+    let cipal_reader = CipalReader::new(cipal.to_str().ok_or(CompareError::InvalidPath)?)?;
+
+    let info = &cipal_reader.info;
+    let kwartalen: HashSet<Kwartaal> = kwartalen(info.begindatum, info.einddatum)?.iter().collect();
+    let cipal_data = cipal_reader.data()?;
+    let loonbestand = cipal_totalen(&cipal_data, mapping);
+    let dmfa = dmfa_totalen(&dmfa_reader.data()?);
+
+    Ok(Vergelijking {
+        verschillen: compare(&loonbestand, &dmfa, &kwartalen),
+        niet_gemapt: mapping.niet_gemapt(&cipal_data),
+    })
+}
+
+/// Compares the DMFA with the BOSA loonbestand.
+///
+/// Both files carry the KBO number of the employer, which has to be the same. Only the quarters present in
+/// the BOSA file are compared, and only the lines with a difference are returned.
+///
+/// # Errors
+///
+/// Returns `CompareError` if one of the files can not be read or the KBO numbers do not match.
+pub fn compare_dmfa_bosa(dmfa: &Path, bosa: &Path) -> Result<Vec<Verschil>, CompareError> {
+    let dmfa_reader = DmfaReader::new(dmfa.to_str().ok_or(CompareError::InvalidPath)?)?;
+    let bosa_reader = BosaReader::new(bosa.to_str().ok_or(CompareError::InvalidPath)?)?;
+
+    if dmfa_reader.kbo_nummer != bosa_reader.kbo_nummer {
+        return Err(CompareError::KboMismatch {
+            dmfa: dmfa_reader.kbo_nummer,
+            bosa: bosa_reader.kbo_nummer,
+        });
+    }
+
+    let bosa_data = bosa_reader.data()?;
+    let kwartalen: HashSet<Kwartaal> = bosa_data.values()
+        .flat_map(|kwartalen| kwartalen.keys().cloned())
+        .collect();
+    let loonbestand = bosa_totalen(&bosa_data);
+    let dmfa = dmfa_totalen(&dmfa_reader.data()?);
+
+    Ok(compare(&loonbestand, &dmfa, &kwartalen))
+}
+
+/// Sums the DMFA wage lines per `LoonKey`.
+pub fn dmfa_totalen(data: &HashMap<Rrn, HashMap<Kwartaal, Vec<DmfaEntry>>>) -> LoonTotalen {
+    let mut totalen = LoonTotalen::new();
+
+    for entry in data.values().flat_map(|kwartalen| kwartalen.values()).flatten() {
+        let key = LoonKey {
+            kwart: entry.kwart.clone(),
+            wgc: entry.wgc,
+            wnk: entry.wnk,
+            insz: entry.insz.clone(),
+            lc: entry.lc,
+        };
+        *totalen.entry(key).or_default() += entry.brutto_loon;
+    }
+
+    totalen
+}
+
+/// Sums the monthly CIPAL lines per quarter and `LoonKey`, with the looncode given by the mapping.
+///
+/// Lines excluded by the mapping (e.g. volunteers without werkgever cathegorie) or not mapped at all are
+/// skipped, see `Mapping::niet_gemapt` for the latter.
+pub fn cipal_totalen(entries: &[CipalEntry], mapping: &Mapping) -> LoonTotalen {
+    let mut totalen = LoonTotalen::new();
+
+    for entry in entries {
+        let Toewijzing::Looncode(lc) = mapping.toewijzing(entry) else {
+            continue;
+        };
+
+        let key = LoonKey {
+            kwart: entry.maand.to_kwartaal(),
+            wgc: entry.wgc,
+            wnk: entry.wnk,
+            insz: entry.insz.clone(),
+            lc,
+        };
+        *totalen.entry(key).or_default() += entry.brutobedrag;
+    }
+
+    totalen
+}
+
+/// Sums the monthly BOSA lines per quarter and `LoonKey`.
+pub fn bosa_totalen(data: &HashMap<Rrn, HashMap<Kwartaal, Vec<BosaEntry>>>) -> LoonTotalen {
+    let mut totalen = LoonTotalen::new();
+
+    for entry in data.values().flat_map(|kwartalen| kwartalen.values()).flatten() {
+        let key = LoonKey {
+            kwart: entry.maand.to_kwartaal(),
+            wgc: entry.wgc,
+            wnk: entry.wnk,
+            insz: entry.insz.clone(),
+            lc: entry.lc,
+        };
+        *totalen.entry(key).or_default() += entry.brutto_loon;
+    }
+
+    totalen
+}
+
+/// Compares the loonbestand with the DMFA for the given quarters.
+///
+/// Returns the lines with a difference of at least one cent, sorted by INSZ, Kwartaal, WGC, WNK and LC.
+pub fn compare(loonbestand: &LoonTotalen, dmfa: &LoonTotalen, kwartalen: &HashSet<Kwartaal>) -> Vec<Verschil> {
+    let keys: HashSet<&LoonKey> = loonbestand.keys()
+        .chain(dmfa.keys())
+        .filter(|key| kwartalen.contains(&key.kwart))
+        .collect();
+
+    let mut verschillen: Vec<Verschil> = keys.into_iter()
+        .map(|key| Verschil {
+            key: key.clone(),
+            loonbestand: loonbestand.get(key).copied().unwrap_or_default(),
+            dmfa: dmfa.get(key).copied().unwrap_or_default(),
+        })
+        .filter(|verschil| !verschil.verschil().is_zero())
+        .collect();
+
+    verschillen.sort_by_key(|verschil| {
+        let key = &verschil.key;
+        (key.insz.clone(), key.kwart.clone(), key.wgc, key.wnk, key.lc)
+    });
+
+    verschillen
+}
+
+/// The loonbestand and DMFA totals of the differences per period, e.g. per year for a yearly overview.
+///
+/// # Errors
+///
+/// Returns `PeriodError::InvalidGranularity` for `Granularity::Maand`, as the DMFA is declared per quarter.
+pub fn totalen_per_periode(verschillen: &[Verschil], granularity: Granularity) -> Result<BTreeMap<Period, (Money, Money)>, PeriodError> {
+    let mut totalen: BTreeMap<Period, (Money, Money)> = BTreeMap::new();
+    for verschil in verschillen {
+        let periode = Period::from(verschil.key.kwart.clone()).to(granularity)?;
+        let (loonbestand, dmfa) = totalen.entry(periode).or_default();
+        *loonbestand += verschil.loonbestand;
+        *dmfa += verschil.dmfa;
+    }
+
+    Ok(totalen)
+}
+
+/// The quarters between two dates (both included).
+///
+/// # Errors
+///
+/// Returns `KwartaalError::InvalidRange` if `begin` comes after `eind`.
+pub fn kwartalen(begin: Date, eind: Date) -> Result<KwartaalRange, KwartaalError> {
+    KwartaalRange::new(Kwartaal::from_date(begin), Kwartaal::from_date(eind))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::bosa_file;
+    use time::Month;
+
+    fn key(kwart: &str, wnk: u16, insz: &str, lc: u16) -> LoonKey {
+        LoonKey {
+            kwart: Kwartaal::new(kwart.to_string()).unwrap(),
+            wgc: 751,
+            wnk,
+            insz: Rrn::new(insz).unwrap(),
+            lc,
+        }
+    }
+
+    #[test]
+    fn test_kwartalen() {
+        let begin = Date::from_calendar_date(2022, Month::November, 1).unwrap();
+        let eind = Date::from_calendar_date(2023, Month::April, 30).unwrap();
+        let range = kwartalen(begin, eind).unwrap();
+        assert_eq!(range.len(), 3);
+        assert!(range.contains(&Kwartaal::new("20224".to_string()).unwrap()));
+        assert!(range.contains(&Kwartaal::new("20231".to_string()).unwrap()));
+        assert!(range.contains(&Kwartaal::new("20232".to_string()).unwrap()));
+
+        // The begindatum after the einddatum is an error, not an empty span of quarters
+        assert_eq!(kwartalen(eind, begin), Err(KwartaalError::InvalidRange));
+    }
+
+    fn bedrag(bedrag: &str) -> Money {
+        bedrag.parse().unwrap()
+    }
+
+    #[test]
+    fn test_compare() {
+        let loonbestand = LoonTotalen::from([
+            (key("20231", 15, "50062049792", 1), bedrag("6956.34")),
+            (key("20231", 15, "65040644490", 1), bedrag("4285.2")),
+            (key("20231", 15, "62011246695", 2), bedrag("97.03")),
+            (key("20221", 15, "62011246695", 1), bedrag("100.0")),
+        ]);
+        let dmfa = LoonTotalen::from([
+            (key("20231", 15, "50062049792", 1), bedrag("6856.35")),
+            (key("20231", 15, "65040644490", 1), bedrag("4285.2")),
+            (key("20231", 15, "67033032905", 1), bedrag("4553.09")),
+        ]);
+        let kwartalen = HashSet::from([Kwartaal::new("20231".to_string()).unwrap()]);
+
+        let verschillen = compare(&loonbestand, &dmfa, &kwartalen);
+        assert_eq!(verschillen.len(), 3);
+        assert_eq!(verschillen[0].key, key("20231", 15, "50062049792", 1));
+        assert_eq!(verschillen[0].verschil(), bedrag("99.99"));
+        assert_eq!(verschillen[1].key, key("20231", 15, "62011246695", 2));
+        assert_eq!(verschillen[1].dmfa, Money::ZERO);
+        assert_eq!(verschillen[2].verschil(), bedrag("-4553.09"));
+    }
+
+    #[test]
+    fn test_totalen_per_periode() {
+        let verschil = |kwart: &str, loonbestand: &str, dmfa: &str| Verschil {
+            key: key(kwart, 15, "50062049792", 1),
+            loonbestand: bedrag(loonbestand),
+            dmfa: bedrag(dmfa),
+        };
+        let verschillen = [
+            verschil("20224", "100.0", "90.0"),
+            verschil("20231", "200.1", "100.0"),
+            verschil("20233", "0.2", "50.0"),
+        ];
+
+        let per_kwartaal = totalen_per_periode(&verschillen, Granularity::Kwartaal).unwrap();
+        assert_eq!(per_kwartaal.len(), 3);
+
+        let per_jaar = totalen_per_periode(&verschillen, Granularity::Jaar).unwrap();
+        assert_eq!(per_jaar.into_iter().collect::<Vec<_>>(), [
+            (Period::Jaar(2022), (bedrag("100.0"), bedrag("90.0"))),
+            (Period::Jaar(2023), (bedrag("200.3"), bedrag("150.0"))),
+        ]);
+
+        assert_eq!(
+            totalen_per_periode(&verschillen, Granularity::Maand).unwrap_err(),
+            PeriodError::InvalidGranularity { from: Granularity::Kwartaal, to: Granularity::Maand }
+        );
+    }
+
+    #[test]
+    fn test_compare_dmfa_cipal() {
+        let vergelijking = compare_dmfa_cipal(
+            Path::new("tests/fixtures/207527540-dmfa.xlsx"),
+            Path::new("tests/fixtures/207527540-cipal.xlsx"),
+            &Mapping::standaard(),
+        ).unwrap();
+        let verschillen = &vergelijking.verschillen;
+
+        let verschil = verschillen.iter()
+            .find(|verschil| verschil.key == key("20231", 15, "50062049792", 1))
+            .unwrap();
+        assert_eq!(verschil.loonbestand, bedrag("6956.34"));
+        assert_eq!(verschil.dmfa, bedrag("6856.35"));
+        assert_eq!(verschil.verschil(), bedrag("99.99"));
+        assert!(verschillen.iter().all(|verschil| verschil.key.kwart.year == 2023));
+        assert_eq!(verschillen.len(), 60);
+        assert!(vergelijking.niet_gemapt.is_empty());
+    }
+
+    #[test]
+    fn test_compare_dmfa_cipal_niet_gemapt() {
+        let mapping = Mapping::from_toml("[[uitsluiting]]\nwgc = 0\n\n[[regel]]\nbezoldigingscode = 1\n").unwrap();
+        let vergelijking = compare_dmfa_cipal(
+            Path::new("tests/fixtures/207527540-dmfa.xlsx"),
+            Path::new("tests/fixtures/207527540-cipal.xlsx"),
+            &mapping,
+        ).unwrap();
+
+        let codes: Vec<u16> = vergelijking.niet_gemapt.iter().map(|lijn| lijn.bezoldigingscode).collect();
+        assert!(codes.contains(&0));
+        assert!(codes.contains(&999));
+        assert!(!codes.contains(&1));
+        assert!(vergelijking.verschillen.iter()
+            .filter(|verschil| verschil.key.lc != 1)
+            .all(|verschil| verschil.loonbestand.is_zero()));
+    }
+
+    #[test]
+    fn test_compare_dmfa_bosa() {
+        let bosa = bosa_file(&[
+            ["208322049", "80080703041", "202310", "1", "495", "2", "1.217,21"],
+            ["208322049", "80080703041", "202311", "1", "495", "2", "1.217,22"],
+        ]);
+        let verschillen = compare_dmfa_bosa(Path::new("tests/fixtures/208322049-dmfa.xlsx"), bosa.path()).unwrap();
+
+        let mut key = key("20234", 495, "80080703041", 2);
+        key.wgc = 1;
+        let verschil = verschillen.iter().find(|verschil| verschil.key == key).unwrap();
+        assert_eq!(verschil.loonbestand, bedrag("2434.43"));
+        assert_eq!(verschil.dmfa, bedrag("2390.34"));
+        assert_eq!(verschil.verschil(), bedrag("44.09"));
+        assert!(verschillen.iter().all(|verschil| verschil.key.kwart == key.kwart));
+    }
+
+    #[test]
+    fn test_compare_dmfa_bosa_kbo_mismatch() {
+        let bosa = bosa_file(&[
+            ["208322049", "80080703041", "202310", "1", "495", "2", "1.217,21"],
+        ]);
+        let result = compare_dmfa_bosa(Path::new("tests/fixtures/207527540-dmfa.xlsx"), bosa.path());
+        assert_eq!(result.unwrap_err(), CompareError::KboMismatch {
+            dmfa: Kbo::new("207527540").unwrap(),
+            bosa: Kbo::new("208322049").unwrap(),
+        });
+    }
+}
@@ -0,0 +1,199 @@
+// Diagnose : the invalid cells of a document, with their place (file, sheet, A1 reference), raw value and
+// expected type, and a copy of the document with these cells highlighted.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+use umya_spreadsheet::Comment;
+use crate::schema::Kolomtype;
+use crate::xlsx::{cell_reference, Row};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum DiagnoseError {
+    #[error("Could not read {0}.")]
+    ReadError(String),
+    #[error("Sheet {0} not found.")]
+    SheetNotFound(String),
+    #[error("Could not write {0}.")]
+    WriteError(String),
+}
+
+/// A cell that could not be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellError {
+    pub file: PathBuf,
+    pub sheet: String,
+    /// The 1-based column.
+    pub column: u32,
+    /// The 1-based row.
+    pub row: u32,
+    /// The name of the column, as in the schema.
+    pub name: String,
+    /// The raw value of the cell.
+    pub value: String,
+    /// The expected type.
+    pub expected: Kolomtype,
+}
+
+impl CellError {
+    /// The A1 reference of the cell, e.g. `F12`.
+    pub fn cell(&self) -> String {
+        cell_reference(self.column, self.row)
+    }
+}
+
+impl fmt::Display for CellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f, "{} {}!{}: invalid {} {:?} in column {}.",
+            self.file.display(), self.sheet, self.cell(), self.expected, self.value, self.name
+        )
+    }
+}
+
+/// Parses the cells of one row, collecting every invalid cell.
+pub(crate) struct RowReader<'a> {
+    row: &'a Row,
+    file: &'a Path,
+    sheet: &'a str,
+    errors: Vec<CellError>,
+}
+
+impl<'a> RowReader<'a> {
+    pub(crate) fn new(row: &'a Row, file: &'a Path, sheet: &'a str) -> Self {
+        RowReader { row, file, sheet, errors: Vec::new() }
+    }
+
+    /// Parses a cell with `parse`, an invalid cell gives `None` and is recorded.
+    pub(crate) fn read<T>(
+        &mut self,
+        column: u32,
+        name: &str,
+        expected: Kolomtype,
+        parse: impl FnOnce(&str) -> Option<T>,
+    ) -> Option<T> {
+        let value = self.row.get(column);
+        let result = parse(value);
+        if result.is_none() {
+            self.errors.push(CellError {
+                file: self.file.to_path_buf(),
+                sheet: self.sheet.to_string(),
+                column,
+                row: self.row.number,
+                name: name.to_string(),
+                value: value.to_string(),
+                expected,
+            });
+        }
+        result
+    }
+
+    /// Parses a cell with `FromStr`.
+    pub(crate) fn value<T: FromStr>(&mut self, column: u32, name: &str, expected: Kolomtype) -> Option<T> {
+        self.read(column, name, expected, |value| value.trim().parse().ok())
+    }
+
+    /// Parses a cell that may be empty with `FromStr`.
+    pub(crate) fn optional<T: FromStr>(&mut self, column: u32, name: &str, expected: Kolomtype) -> Option<Option<T>> {
+        if self.row.get(column).trim().is_empty() {
+            return Some(None);
+        }
+        self.value(column, name, expected).map(Some)
+    }
+
+    /// Ends the row : a strict reader stops at the first invalid cell, a lenient one skips the row and keeps
+    /// all its invalid cells in `errors`.
+    pub(crate) fn finish(self, lenient: bool, errors: &mut Vec<CellError>) -> Result<(), CellError> {
+        match self.errors.first() {
+            Some(error) if !lenient => Err(error.clone()),
+            _ => {
+                errors.extend(self.errors);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Writes a copy of a workbook with the invalid cells filled in red, each with a comment telling what is wrong.
+///
+/// # Arguments
+///
+/// * `file` - The workbook the cells are from.
+/// * `errors` - The invalid cells, as collected by the lenient readers.
+/// * `output` - The path of the copy.
+///
+/// # Errors
+///
+/// Returns `DiagnoseError` if the workbook can not be read, a sheet of the cells is missing, or the copy can not
+/// be written.
+pub fn highlight(file: &Path, errors: &[CellError], output: &Path) -> Result<(), DiagnoseError> {
+    let mut book = umya_spreadsheet::reader::xlsx::read(file)
+        .map_err(|_| DiagnoseError::ReadError(file.display().to_string()))?;
+
+    for error in errors {
+        let sheet = book.get_sheet_by_name_mut(&error.sheet)
+            .ok_or_else(|| DiagnoseError::SheetNotFound(error.sheet.clone()))?;
+        sheet.get_style_mut((error.column, error.row)).set_background_color("FFFF9999");
+
+        let mut comment = Comment::default();
+        comment.new_comment((error.column, error.row))
+            .set_author("lvgl")
+            .set_text_string(format!("Invalid {} in column {}.", error.expected, error.name));
+        sheet.add_comments(comment);
+    }
+
+    umya_spreadsheet::writer::xlsx::write(&book, output)
+        .map_err(|_| DiagnoseError::WriteError(output.display().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::Builder;
+    use crate::test_util::xlsx_file;
+
+    fn cell_error(file: &Path) -> CellError {
+        CellError {
+            file: file.to_path_buf(),
+            sheet: "Sheet1".to_string(),
+            column: 2,
+            row: 12,
+            name: "Bedrag".to_string(),
+            value: "veel".to_string(),
+            expected: Kolomtype::Bedrag,
+        }
+    }
+
+    #[test]
+    fn test_cell_error() {
+        let error = cell_error(Path::new("bosa.xlsx"));
+        assert_eq!(error.cell(), "B12");
+        assert_eq!(error.to_string(), "bosa.xlsx Sheet1!B12: invalid bedrag \"veel\" in column Bedrag.");
+    }
+
+    #[test]
+    fn test_highlight() {
+        let mut rows = vec![vec![]; 11];
+        rows.push(vec!["", "veel"]);
+        let file = xlsx_file(&rows);
+
+        let output = Builder::new().suffix(".xlsx").tempfile().unwrap();
+        highlight(file.path(), &[cell_error(file.path())], output.path()).unwrap();
+
+        let book = umya_spreadsheet::reader::xlsx::read(output.path()).unwrap();
+        let sheet = book.get_sheet_by_name("Sheet1").unwrap();
+        assert_eq!(sheet.get_value((2, 12)), "veel");
+        let color = sheet.get_style((2, 12)).get_background_color().map(|color| color.get_argb().to_string());
+        assert_eq!(color.as_deref(), Some("FFFF9999"));
+        let comments = sheet.get_comments();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].get_coordinate().to_string(), "B12");
+        // The text of a comment is not public, only its debug output shows it
+        assert!(format!("{:?}", comments[0].get_text()).contains("Invalid bedrag in column Bedrag."));
+
+        let mut error = cell_error(file.path());
+        error.sheet = "Data".to_string();
+        assert_eq!(highlight(file.path(), &[error], output.path()), Err(DiagnoseError::SheetNotFound("Data".to_string())));
+    }
+}
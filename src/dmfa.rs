@@ -1,12 +1,15 @@
 // DMFA = Déclaration MultiFonctionnelle & MultiFunctionele Aangifte
 // https://www.socialsecurity.be/site_en/employer/applics/dmfa/documents/pdf/brochure_dmfa.pdf
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use umya_spreadsheet::reader::xlsx;
-use umya_spreadsheet::Spreadsheet;
 use thiserror::Error;
-use crate::types::{Rrn, RrnError};
+use crate::bron::{require_xlsx, BronError};
+use crate::diagnose::{CellError, RowReader};
+use crate::kolommen::DmfaKolom;
+use crate::schema::{Kolomtype, SchemaError, DMFA};
+use crate::types::{Kbo, Kwartaal, KwartaalError, KwartaalRange, Money, Rrn};
+use crate::xlsx::{Row, XlsxReader};
 
 #[derive(Error, Debug, PartialEq)]
 pub enum DmfaError {
@@ -14,8 +17,10 @@ pub enum DmfaError {
     InvalidFilename,
     #[error("File not found.")]
     FileNotFound,
-    #[error("Invalid file extension.")]
-    InvalidExtension,
+    #[error("Invalid file type {0}, expected xlsx.")]
+    InvalidFileType(String),
+    #[error("Invalid xlsx file: {0}")]
+    InvalidXlsx(String),
     #[error("KBO not found.")]
     KboNotFound,
     #[error("Multiple KBO numbers found.")]
@@ -30,21 +35,34 @@ pub enum DmfaError {
     InvalidKbo,
     #[error("Too many KBO numbers")]
     TooManyKbo,
+    #[error("KBO number does not match the sheet name.")]
+    KboMismatch,
+    #[error("Column {0} not found.")]
+    ColumnNotFound(String),
+    #[error("{0}")]
+    InvalidCell(CellError),
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct DmfaEntry {
-    kwart: u16,       // Kwartaal YYYYQ
-    wgc: u16,         // Werkgever cathegorie
-    wnk: u16,         // Werknemer kengetal
-    lc: u16,          // Looncode
-    brutto_loon: f32, // Brutoloon
+    pub kwart: Kwartaal,         // Kwartaal YYYYQ
+    pub insz: Rrn,               // Rijksregister nummer
+    pub wgc: u16,                // Werkgever cathegorie
+    pub wnk: u16,                // Werknemer kengetal
+    pub t_nr: Option<u16>,       // Tewerkstellingsnummer
+    pub t_begin: Option<u32>,    // Begin tewerkstelling (Excel datum)
+    pub t_eind: Option<u32>,     // Einde tewerkstelling (Excel datum)
+    pub lc: u16,                 // Looncode
+    pub brutto_loon: Money,      // Brutoloon
 }
 
+/// The wage lines by INSZ and Kwartaal.
+pub type DmfaData = HashMap<Rrn, HashMap<Kwartaal, Vec<DmfaEntry>>>;
+
 #[derive(Debug)]
 pub struct DmfaReader {
     pub path: PathBuf,
-    pub kbo_nummer: String,
+    pub kbo_nummer: Kbo,
     pub start_kwartaal: String,
     pub stop_kwartaal: String,
 }
@@ -58,8 +76,9 @@ impl DmfaReader {
     ///
     /// # Errors
     ///
-    /// Returns `DmfaError` if the filename is invalid, the file is not found, the file extension is invalid,
-    /// the KBO number is not found, or multiple KBO numbers are found.
+    /// Returns `DmfaError` if the filename is invalid, the file is not found, the file is not an xlsx workbook
+    /// whatever its extension, the KBO number is not found, invalid or multiple KBO numbers are found, or the
+    /// KBO number does not match the employer suffix of the sheet name.
     ///
     /// # Examples
     ///
@@ -67,32 +86,30 @@ impl DmfaReader {
     /// use lvgl::DmfaReader;
     ///
     /// let dmfa_reader = DmfaReader::new("tests/fixtures/207527540-dmfa.xlsx").unwrap();
+    /// assert_eq!(dmfa_reader.kbo_nummer.to_string(), "0207.527.540");
     /// ```
     pub fn new(filename: &str) -> Result<Self, DmfaError> {
         if filename.is_empty() {
             return Err(DmfaError::InvalidFilename);
         }
 
-        // Convert the filename to lowercase and check if it ends with .xlsx
-        if !filename.to_lowercase().ends_with(".xlsx") {
-            return Err(DmfaError::InvalidExtension);
-        }
-
         let path = Path::new(filename).to_path_buf();
 
         if !path.exists() {
             return Err(DmfaError::FileNotFound);
         }
 
-        let book: Spreadsheet = xlsx::read(&path).map_err(|_| DmfaError::FileNotFound)?;
-        let sheet_count = book.get_sheet_count();
-        match sheet_count {
-            0 => Err(DmfaError::NoSheets)?,
-            1 => (),
+        require_xlsx(&path).map_err(|e| match e {
+            BronError::InvalidFileType(soort) => DmfaError::InvalidFileType(soort.to_string()),
+            _ => DmfaError::FileNotFound,
+        })?;
+
+        let mut workbook = XlsxReader::open(&path).map_err(|e| DmfaError::InvalidXlsx(e.to_string()))?;
+        let sheet_name = match workbook.sheet_names().as_slice() {
+            [] => Err(DmfaError::NoSheets)?,
+            [sheet_name] => sheet_name.to_string(),
             _ => Err(DmfaError::TooManySheets)?,
-        }   
-        let sheet = book.get_sheet(&0).ok_or(DmfaError::KboNotFound)?;
-        let sheet_name = sheet.get_name();
+        };
 
         let parts: Vec<&str> = sheet_name.split('_').collect();
         if parts.len() < 3 {
@@ -101,56 +118,42 @@ impl DmfaReader {
         let start_kwartaal = parts[1].to_string();
         let stop_kwartaal = parts[2].to_string();
 
-        // Find the column with "KBO" in the first row
-        let mut kbo_column = None;
-        if let header_columns = sheet.get_collection_by_row_to_hashmap(&1) {
-            println!("{:?}", header_columns);
-
-
-            // for (col_idx, cell) in header_columns.iter().enumerate() {
-            //     println!("{}: {:?}", col_idx, cell.get_value());
-            //     let cell_value = cell.get_value();
-            //     if cell_value.contains("KBO") {
-            //         kbo_column = Some(col_idx + 1); // Save the column index (1-based)
-            //         break;  
-            //     }
-
-                // if let Some(cell_value) = cell.get_value() {
-                //     if cell_value.contains("KBO") {
-                //         kbo_column = Some(col_idx + 1); // Save the column index (1-based)
-                //         break;
-                //     }
-                // }
-            // }
+        let mut rows = workbook.sheet(0).map_err(|_| DmfaError::NoSheets)?;
+        let header: Vec<Row> = rows.by_ref()
+            .take(2)
+            .collect::<Result<_, _>>()
+            .map_err(|e| DmfaError::InvalidXlsx(e.to_string()))?;
+
+        // The KBO column, from the Dutch "KBO" or the French "BCE" header
+        let kbo_column = DMFA.zoek(&header).get(DmfaKolom::Kbo.naam()).ok_or(DmfaError::KboNotFound)?;
+
+        // Collect the distinct values of the KBO column, skipping both header rows
+        let mut kbo_values: HashSet<String> = HashSet::new();
+        for row in header.into_iter().map(Ok).chain(rows) {
+            let row = row.map_err(|e| DmfaError::InvalidXlsx(e.to_string()))?;
+            let value = row.get(kbo_column).trim();
+            if row.number >= 3 && !value.is_empty() && !kbo_values.contains(value) {
+                kbo_values.insert(value.to_string());
+            }
         }
 
-        let kbo_column = kbo_column.ok_or(DmfaError::KboNotFound)?;
-
-        // // Create a vector of all elements in the KBO column from the 3rd row to the end
-        // let mut kbo_values = Vec::new();
-        // for row_idx in 3..=sheet.get_highest_row() {
-        //     if let Some(cell) = sheet.get_cell(row_idx, kbo_column) {
-        //         if let Some(cell_value) = cell.get_value() {
-        //             kbo_values.push(cell_value.clone());
-        //         }
-        //     }
-        // }
-
-        // // Verify that all elements of this vector are the same
-        // if kbo_values.is_empty() {
-        //     return Err(DmfaError::InvalidKbo);
-        // }
-
-        // let first_value = &kbo_values[0];
-        // for value in &kbo_values[1..] {
-        //     if value != first_value {
-        //         return Err(DmfaError::TooManyKbo);
-        //     }
-        // }
-
-        // let kbo_nummer = first_value.clone();
-
-        let kbo_nummer = Ok::<String, DmfaError>("207527540".to_string()).unwrap(); 
+        let kbo_nummers: HashSet<Kbo> = kbo_values.iter()
+            .map(|value| Kbo::new(value))
+            .collect::<Result<_, _>>()
+            .map_err(|_| DmfaError::InvalidKbo)?;
+
+        let kbo_nummer = match kbo_nummers.len() {
+            0 => return Err(DmfaError::KboNotFound),
+            1 => kbo_nummers.into_iter().next().ok_or(DmfaError::KboNotFound)?,
+            _ => return Err(DmfaError::MultipleKbo),
+        };
+
+        // The sheet name ends with the truncated KBO number of the employer (e.g. 020752)
+        if let Some(suffix) = parts.get(3) {
+            if !kbo_matches_sheet_name(&kbo_nummer, suffix) {
+                return Err(DmfaError::KboMismatch);
+            }
+        }
 
         Ok(DmfaReader {
             path,
@@ -160,51 +163,122 @@ impl DmfaReader {
         })
     }
 
-    // pub fn data(&self) -> Result<HashMap<Rrn, HashMap<Kwartaal, DmfaEntry>>, DmfaError> {
-    //     let book: Spreadsheet = xlsx::read(&self.path).map_err(|_| DmfaError::FileNotFound)?;
-    //     let sheet_names = book.get_sheet_names();
-    //     let mut data = HashMap::new();
-
-    //     for sheet_name in sheet_names {
-    //         let sheet = book.get_sheet_by_name(&sheet_name).ok_or(DmfaError::KboNotFound)?;
-    //         let mut sheet_data = HashMap::new();
+    /// The span of quarters in the sheet name, e.g. `20213_20242`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `KwartaalError` if the quarters of the sheet name are invalid.
+    pub fn kwartalen(&self) -> Result<KwartaalRange, KwartaalError> {
+        KwartaalRange::new(self.start_kwartaal.parse()?, self.stop_kwartaal.parse()?)
+    }
 
-    //         for row_idx in 2..=sheet.get_highest_row() {
-    //             let row = sheet.get_row(row_idx).unwrap();
-    //             let kwart = row.get_cell(1).unwrap().get_value().parse().unwrap_or(0);
-    //             let wgc = row.get_cell(2).unwrap().get_value().parse().unwrap_or(0);
-    //             let wnk = row.get_cell(3).unwrap().get_value().parse().unwrap_or(0);
-    //             let lc = row.get_cell(4).unwrap().get_value().parse().unwrap_or(0);
-    //             let brutto_loon = row.get_cell(5).unwrap().get_value().parse().unwrap_or(0.0);
+    /// Reads all wage lines (lines with a looncode) from the DMFA file.
+    ///
+    /// The first two rows are the Dutch and French headers, the columns are found in either of them. Lines
+    /// without a looncode (contribution lines) are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DmfaError::ColumnNotFound` with all the required columns missing from the header, and
+    /// `DmfaError::InvalidCell` with the place, value and expected type of the first cell that can not be parsed.
+    pub fn data(&self) -> Result<DmfaData, DmfaError> {
+        self.read(false).map(|(data, _)| data)
+    }
 
-    //             let entry = DmfaEntry {
-    //                 kwart,
-    //                 wgc,
-    //                 wnk,
-    //                 lc,
-    //                 brutto_loon,
-    //             };
+    /// Reads all wage lines like `data`, but skips the lines with cells that can not be parsed and returns
+    /// these cells.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DmfaError::ColumnNotFound` with all the required columns missing from the header.
+    pub fn data_lenient(&self) -> Result<(DmfaData, Vec<CellError>), DmfaError> {
+        self.read(true)
+    }
 
-    //             sheet_data.insert(format!("row_{}", row_idx), entry);
-    //         }
+    fn read(&self, lenient: bool) -> Result<(DmfaData, Vec<CellError>), DmfaError> {
+        let mut workbook = XlsxReader::open(&self.path).map_err(|e| DmfaError::InvalidXlsx(e.to_string()))?;
+        let sheet_name = workbook.sheet_names().first().map(|name| name.to_string()).ok_or(DmfaError::NoSheets)?;
+        let mut rows = workbook.sheet(0).map_err(|_| DmfaError::NoSheets)?;
+
+        let header: Vec<Row> = rows.by_ref()
+            .take(2)
+            .collect::<Result<_, _>>()
+            .map_err(|e| DmfaError::InvalidXlsx(e.to_string()))?;
+        let kolommen = DMFA.kolommen(&header).map_err(|e| match e {
+            SchemaError::ColumnsNotFound(namen) => DmfaError::ColumnNotFound(namen.join(", ")),
+            _ => DmfaError::FileNotFound,
+        })?;
+        let column = |kolom: DmfaKolom| {
+            kolommen.get(kolom.naam()).ok_or_else(|| DmfaError::ColumnNotFound(kolom.naam().to_string()))
+        };
+
+        let kwart_col = column(DmfaKolom::Kwart)?;
+        let wgc_col = column(DmfaKolom::Wgc)?;
+        let wnk_col = column(DmfaKolom::Wnk)?;
+        let insz_col = column(DmfaKolom::Insz)?;
+        let t_nr_col = column(DmfaKolom::TNr)?;
+        let t_begin_col = column(DmfaKolom::TBegin)?;
+        let t_eind_col = column(DmfaKolom::TEind)?;
+        let lc_col = column(DmfaKolom::Lc)?;
+        let lc_bedr_col = column(DmfaKolom::LcBedr)?;
+
+        let mut data: HashMap<Rrn, HashMap<Kwartaal, Vec<DmfaEntry>>> = HashMap::new();
+        let mut errors = Vec::new();
+
+        for row in rows {
+            let row = row.map_err(|e| DmfaError::InvalidXlsx(e.to_string()))?;
+            if row.number < 3 || row.get(lc_col).trim().is_empty() {
+                continue;
+            }
+
+            let mut cells = RowReader::new(&row, &self.path, &sheet_name);
+            let kwart = cells.read(kwart_col, "Kwart", Kolomtype::Kwartaal, |waarde| Kwartaal::new(waarde.to_string()).ok());
+            let insz = cells.read(insz_col, "INSZ", Kolomtype::Insz, |waarde| Rrn::new_checked(waarde).ok());
+            let wgc = cells.value(wgc_col, "WGC", Kolomtype::Geheel);
+            let wnk = cells.value(wnk_col, "WNK", Kolomtype::Geheel);
+            let t_nr = cells.optional(t_nr_col, "T_nr", Kolomtype::Geheel);
+            let t_begin = cells.optional(t_begin_col, "T_begin", Kolomtype::Geheel);
+            let t_eind = cells.optional(t_eind_col, "T_eind", Kolomtype::Geheel);
+            let lc = cells.value(lc_col, "LC", Kolomtype::Geheel);
+            let brutto_loon = cells.value(lc_bedr_col, "LC_bedr", Kolomtype::Bedrag);
+            cells.finish(lenient, &mut errors).map_err(DmfaError::InvalidCell)?;
+
+            let entry = (|| Some(DmfaEntry {
+                kwart: kwart?,
+                insz: insz?,
+                wgc: wgc?,
+                wnk: wnk?,
+                t_nr: t_nr?,
+                t_begin: t_begin?,
+                t_eind: t_eind?,
+                lc: lc?,
+                brutto_loon: brutto_loon?,
+            }))();
+            if let Some(entry) = entry {
+                data.entry(entry.insz.clone()).or_default().entry(entry.kwart.clone()).or_default().push(entry);
+            }
+        }
 
-    //         data.insert(sheet_name, sheet_data);
-    //     }
+        Ok((data, errors))
+    }
+}
 
-    //     Ok(())
-    // }
+/// Checks the KBO number against the truncated employer number at the end of the sheet name.
+fn kbo_matches_sheet_name(kbo_nummer: &Kbo, suffix: &str) -> bool {
+    !suffix.is_empty() && kbo_nummer.as_str().starts_with(suffix)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::{broken_xlsx, xlsx_sheet};
 
     #[test]
     fn test_dmfa_reader_new_valid() {
         let dmfa_reader = DmfaReader::new("tests/fixtures/207527540-dmfa.xlsx");
         assert!(dmfa_reader.is_ok());
         let dmfa_reader = dmfa_reader.unwrap();
-        assert_eq!(dmfa_reader.kbo_nummer, "207527540");
+        assert_eq!(dmfa_reader.kbo_nummer, Kbo::new("207527540").unwrap());
     }
 
     #[test]
@@ -215,10 +289,11 @@ mod tests {
     }
 
     #[test]
-    fn test_dmfa_reader_new_invalid_extension() {
-        let dmfa_reader = DmfaReader::new("tests/fixtures/207527540-dmfa.txt");
-        assert!(dmfa_reader.is_err());
-        assert_eq!(dmfa_reader.unwrap_err(), DmfaError::InvalidExtension);
+    fn test_dmfa_reader_new_other_extension() {
+        // The content decides, not the extension
+        let file = tempfile::Builder::new().suffix(".dat").tempfile().unwrap();
+        std::fs::copy("tests/fixtures/207527540-dmfa.xlsx", file.path()).unwrap();
+        assert!(DmfaReader::new(file.path().to_str().unwrap()).is_ok());
     }
 
     #[test]
@@ -228,6 +303,22 @@ mod tests {
         assert_eq!(dmfa_reader.unwrap_err(), DmfaError::FileNotFound);
     }
 
+    #[test]
+    fn test_dmfa_reader_new_invalid_xlsx() {
+        let file = broken_xlsx();
+        let dmfa_reader = DmfaReader::new(file.path().to_str().unwrap());
+        let expected = "Invalid xlsx file: xl/_rels/workbook.xml.rels not found";
+        assert_eq!(dmfa_reader.unwrap_err(), DmfaError::InvalidXlsx(expected.to_string()));
+    }
+
+    #[test]
+    fn test_dmfa_reader_new_invalid_file_type() {
+        let file = tempfile::Builder::new().suffix(".xlsx").tempfile().unwrap();
+        std::fs::write(file.path(), "Kwart;RSZnr;KBO\n20231;12345678;0207527540\n").unwrap();
+        let dmfa_reader = DmfaReader::new(file.path().to_str().unwrap());
+        assert_eq!(dmfa_reader.unwrap_err(), DmfaError::InvalidFileType("csv".to_string()));
+    }
+
     #[test]
     fn test_dmfa_reader_new_no_sheets() {
         let dmfa_reader = DmfaReader::new("tests/fixtures/no_sheets.xlsx");
@@ -241,6 +332,91 @@ mod tests {
         assert!(dmfa_reader.is_err());
         assert_eq!(dmfa_reader.unwrap_err(), DmfaError::TooManySheets);
     }
-}
 
+    #[test]
+    fn test_dmfa_reader_new_other_kbo() {
+        let dmfa_reader = DmfaReader::new("tests/fixtures/208322049-dmfa.xlsx").unwrap();
+        assert_eq!(dmfa_reader.kbo_nummer, Kbo::new("208322049").unwrap());
+        assert_eq!(dmfa_reader.start_kwartaal, "20221");
+        assert_eq!(dmfa_reader.stop_kwartaal, "20241");
+        assert_eq!(dmfa_reader.kwartalen().unwrap().len(), 9);
+    }
+
+    #[test]
+    fn test_kbo_matches_sheet_name() {
+        let kbo_207527540 = Kbo::new("207527540").unwrap();
+        let kbo_208322049 = Kbo::new("0208322049").unwrap();
+        assert!(kbo_matches_sheet_name(&kbo_207527540, "020752"));
+        assert!(kbo_matches_sheet_name(&kbo_208322049, "020832"));
+        assert!(!kbo_matches_sheet_name(&kbo_208322049, "020752"));
+        assert!(!kbo_matches_sheet_name(&kbo_207527540, ""));
+    }
+
+    #[test]
+    fn test_dmfa_reader_data() {
+        let data = DmfaReader::new("tests/fixtures/207527540-dmfa.xlsx").unwrap().data().unwrap();
+        let lines: usize = data.values().flat_map(|kwartalen| kwartalen.values()).map(Vec::len).sum();
+        assert_eq!(lines, 1046);
+
+        let rrn = Rrn::new("50062049792").unwrap();
+        let kwartaal = Kwartaal::new("20231".to_string()).unwrap();
+        let entries = &data[&rrn][&kwartaal];
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].wgc, 751);
+        assert_eq!(entries[0].wnk, 15);
+        assert_eq!(entries[0].lc, 1);
+        assert_eq!(entries[0].t_nr, Some(1));
+        assert_eq!(entries[0].brutto_loon, Money::from_cents(685635));
+    }
+
+    #[test]
+    fn test_dmfa_reader_data_leading_zeros() {
+        let data = DmfaReader::new("tests/fixtures/208322049-dmfa.xlsx").unwrap().data().unwrap();
+        assert!(data.contains_key(&Rrn::new("00011820963").unwrap()));
+    }
 
+    #[test]
+    fn test_dmfa_reader_french_header() {
+        // An export of a French-speaking employer, only the French header row can be trusted
+        let rows = [
+            ["?", "?", "?", "?", "?", "?", "?", "?", "?", "?", "?"],
+            ["Trim", "NOSS", "BCE", "Cat_empl", "Code_trav", "NISS", "Nbr_occup", "Occ_début", "Occ_fin", "Code_rém", "Rém"],
+            ["20231", "12345678", "0207527540", "751", "15", "50062049792", "1", "44927", "45016", "1", "6856.35"],
+        ];
+        let file = xlsx_sheet("DECLARATIONS_20231_20234_020752", &rows);
+
+        let dmfa_reader = DmfaReader::new(file.path().to_str().unwrap()).unwrap();
+        assert_eq!(dmfa_reader.kbo_nummer, Kbo::new("207527540").unwrap());
+        let data = dmfa_reader.data().unwrap();
+        let entries = &data[&Rrn::new("50062049792").unwrap()][&Kwartaal::new("20231".to_string()).unwrap()];
+        assert_eq!(entries[0].wnk, 15);
+        assert_eq!(entries[0].t_begin, Some(44927));
+        assert_eq!(entries[0].brutto_loon, Money::from_cents(685635));
+        assert_eq!(crate::bron::detect(file.path()), Ok(crate::bron::Bron::Dmfa));
+    }
+
+    #[test]
+    fn test_dmfa_reader_data_lenient() {
+        let rows = [
+            ["Kwart", "RSZnr", "KBO", "WGC", "WNK", "INSZ", "T_nr", "T_begin", "T_eind", "LC", "LC_bedr"],
+            ["Trim", "NOSS", "BCE", "Cat_empl", "Code_trav", "NISS", "Nbr_occup", "Occ_début", "Occ_fin", "Code_rém", "Rém"],
+            ["20231", "12345678", "0207527540", "751", "15", "50062049792", "1", "44927", "45016", "1", "6856.35"],
+            ["20235", "12345678", "0207527540", "751", "15", "50062049792", "een", "", "", "1", "10.00"],
+            ["20232", "12345678", "0207527540", "751", "15", "NISS", "1", "", "", "1", "10.00"],
+        ];
+        let file = xlsx_sheet("DECLARATIES_20231_20234_020752", &rows);
+
+        let dmfa_reader = DmfaReader::new(file.path().to_str().unwrap()).unwrap();
+        let (data, errors) = dmfa_reader.data_lenient().unwrap();
+        assert_eq!(data.len(), 1);
+        let cells: Vec<String> = errors.iter().map(|error| error.cell()).collect();
+        assert_eq!(cells, ["A4", "G4", "F5"]);
+        assert_eq!(errors[0].sheet, "DECLARATIES_20231_20234_020752");
+        assert_eq!(errors[0].value, "20235");
+        assert_eq!(errors[0].expected, Kolomtype::Kwartaal);
+        assert_eq!(errors[1].name, "T_nr");
+        assert_eq!(errors[2].expected, Kolomtype::Insz);
+
+        assert_eq!(dmfa_reader.data().unwrap_err(), DmfaError::InvalidCell(errors[0].clone()));
+    }
+}
@@ -0,0 +1,501 @@
+// Flat tables of the wage lines and the differences, to hand them over to other tools (e.g. BI) as CSV,
+// newline-delimited JSON or Parquet without going through Excel.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+use polars::prelude::{Column, DataFrame, NamedFrom, ParquetWriter, Series};
+use serde_json::{Map, Value as JsonValue};
+use thiserror::Error;
+use time::{Date, Duration, Month};
+use crate::bosa::BosaEntry;
+use crate::cipal::CipalEntry;
+use crate::compare::Verschil;
+use crate::dmfa::DmfaEntry;
+use crate::privacy::{Privacy, PrivacyProfile};
+use crate::tolerantie::Klasse;
+use crate::types::{Kwartaal, Money, Rrn};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ExportError {
+    #[error("Invalid format {0}, expected csv, ndjson, json or parquet.")]
+    InvalidFormat(String),
+    #[error("Could not write {0}.")]
+    WriteError(String),
+}
+
+/// The file formats a `Table` can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    /// Newline-delimited JSON, one object per row.
+    Ndjson,
+    /// A JSON array with an object per row.
+    Json,
+    Parquet,
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Ndjson => "ndjson",
+            Format::Json => "json",
+            Format::Parquet => "parquet",
+        }
+    }
+
+    /// The format of a file, given by its extension.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExportError::InvalidFormat` if the extension is not one of the formats.
+    pub fn from_path(path: &str) -> Result<Self, ExportError> {
+        let extension = Path::new(path).extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+        extension.parse().map_err(|_| ExportError::InvalidFormat(path.to_string()))
+    }
+}
+
+impl FromStr for Format {
+    type Err = ExportError;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.trim().to_lowercase().as_str() {
+            "csv" => Ok(Format::Csv),
+            "ndjson" | "jsonl" => Ok(Format::Ndjson),
+            "json" => Ok(Format::Json),
+            "parquet" => Ok(Format::Parquet),
+            _ => Err(ExportError::InvalidFormat(format.to_string())),
+        }
+    }
+}
+
+/// The type of a column : text (UTF-8), a whole number (Int64), or an amount in euro rounded to the cent
+/// (Float64 in Parquet and JSON, two decimals in CSV).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    Text,
+    Integer,
+    Amount,
+}
+
+/// One cell of a `Table`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Integer(i64),
+    Amount(Money),
+    Empty,
+}
+
+impl Value {
+    fn text(&self) -> String {
+        match self {
+            Value::Text(text) => text.clone(),
+            Value::Integer(number) => number.to_string(),
+            Value::Amount(amount) => amount.to_string(),
+            Value::Empty => String::new(),
+        }
+    }
+
+    fn json(&self) -> JsonValue {
+        match self {
+            Value::Text(text) => JsonValue::from(text.as_str()),
+            Value::Integer(number) => JsonValue::from(*number),
+            Value::Amount(amount) => JsonValue::from(amount.to_f64()),
+            Value::Empty => JsonValue::Null,
+        }
+    }
+}
+
+impl<T: Into<i64>> From<Option<T>> for Value {
+    fn from(number: Option<T>) -> Self {
+        number.map_or(Value::Empty, |number| Value::Integer(number.into()))
+    }
+}
+
+/// A table with named and typed columns, the rows hold one `Value` per column.
+///
+/// The columns of the tables below are a stable schema : the same names (snake_case) and types in every
+/// format, and columns that mean the same in several sources (insz, kwartaal, maand, wgc, wnk, lc, bedrag)
+/// have the same name. Empty cells are null in JSON and Parquet.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Table {
+    pub columns: Vec<(&'static str, ValueType)>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl Table {
+    pub fn new(columns: &[(&'static str, ValueType)]) -> Self {
+        Table { columns: columns.to_vec(), rows: Vec::new() }
+    }
+
+    /// The differences of a comparison with their class, INSZ as given by the privacy profile.
+    pub fn verschillen(verschillen: &[(Verschil, Klasse)], privacy: &Privacy) -> Self {
+        let mut table = Table::new(&[
+            ("kwartaal", ValueType::Text),
+            ("wgc", ValueType::Integer),
+            ("wnk", ValueType::Integer),
+            ("insz", ValueType::Text),
+            ("lc", ValueType::Integer),
+            ("loonbestand", ValueType::Amount),
+            ("dmfa", ValueType::Amount),
+            ("verschil", ValueType::Amount),
+            ("klasse", ValueType::Text),
+        ]);
+        for (verschil, klasse) in verschillen {
+            let key = &verschil.key;
+            table.rows.push(vec![
+                Value::Text(key.kwart.to_string()),
+                Value::Integer(key.wgc.into()),
+                Value::Integer(key.wnk.into()),
+                Value::Text(privacy.insz(&key.insz)),
+                Value::Integer(key.lc.into()),
+                Value::Amount(verschil.loonbestand),
+                Value::Amount(verschil.dmfa),
+                Value::Amount(verschil.verschil()),
+                Value::Text(klasse.to_string()),
+            ]);
+        }
+        table
+    }
+
+    /// The DMFA wage lines, sorted by INSZ and Kwartaal.
+    pub fn dmfa(data: &HashMap<Rrn, HashMap<Kwartaal, Vec<DmfaEntry>>>, privacy: &Privacy) -> Self {
+        let mut table = Table::new(&[
+            ("kwartaal", ValueType::Text),
+            ("insz", ValueType::Text),
+            ("wgc", ValueType::Integer),
+            ("wnk", ValueType::Integer),
+            ("tewerkstelling", ValueType::Integer),
+            ("begin", ValueType::Text),
+            ("einde", ValueType::Text),
+            ("lc", ValueType::Integer),
+            ("bedrag", ValueType::Amount),
+        ]);
+        for entry in sorted(data) {
+            table.rows.push(vec![
+                Value::Text(entry.kwart.to_string()),
+                Value::Text(privacy.insz(&entry.insz)),
+                Value::Integer(entry.wgc.into()),
+                Value::Integer(entry.wnk.into()),
+                entry.t_nr.into(),
+                excel_date(entry.t_begin),
+                excel_date(entry.t_eind),
+                Value::Integer(entry.lc.into()),
+                Value::Amount(entry.brutto_loon),
+            ]);
+        }
+        table
+    }
+
+    /// The CIPAL wage lines, in the order of the export. The names are left empty unless the privacy
+    /// profile is clear.
+    pub fn cipal(entries: &[CipalEntry], privacy: &Privacy) -> Self {
+        let mut table = Table::new(&[
+            ("maand", ValueType::Text),
+            ("kwartaal", ValueType::Text),
+            ("insz", ValueType::Text),
+            ("naam", ValueType::Text),
+            ("voornaam", ValueType::Text),
+            ("persnr", ValueType::Integer),
+            ("wgc", ValueType::Integer),
+            ("wnk", ValueType::Integer),
+            ("aard", ValueType::Integer),
+            ("code", ValueType::Integer),
+            ("extensie", ValueType::Integer),
+            ("bezoldigingscode", ValueType::Integer),
+            ("omschrijving", ValueType::Text),
+            ("kostenplaats", ValueType::Integer),
+            ("bedrag", ValueType::Amount),
+            ("patr_rsz", ValueType::Amount),
+            ("patr_pens", ValueType::Amount),
+        ]);
+        let name = |name: &String| match privacy.profile {
+            PrivacyProfile::Clear => Value::Text(name.clone()),
+            _ => Value::Empty,
+        };
+        for entry in entries {
+            table.rows.push(vec![
+                Value::Text(entry.maand.to_string()),
+                Value::Text(entry.maand.to_kwartaal().to_string()),
+                Value::Text(privacy.insz(&entry.insz)),
+                name(&entry.naam),
+                name(&entry.voornaam),
+                Value::Integer(entry.persnr.into()),
+                Value::Integer(entry.wgc.into()),
+                Value::Integer(entry.wnk.into()),
+                Value::Integer(entry.aard.into()),
+                Value::Integer(entry.code.into()),
+                Value::Integer(entry.extensie.into()),
+                Value::Integer(entry.bezoldigingscode.into()),
+                Value::Text(entry.omschrijving.clone()),
+                Value::Integer(entry.kostenplaats.into()),
+                Value::Amount(entry.brutobedrag),
+                Value::Amount(entry.patr_rsz),
+                Value::Amount(entry.patr_pens),
+            ]);
+        }
+        table
+    }
+
+    /// The BOSA wage lines, sorted by INSZ and Kwartaal.
+    pub fn bosa(data: &HashMap<Rrn, HashMap<Kwartaal, Vec<BosaEntry>>>, privacy: &Privacy) -> Self {
+        let mut table = Table::new(&[
+            ("maand", ValueType::Text),
+            ("kwartaal", ValueType::Text),
+            ("insz", ValueType::Text),
+            ("wgc", ValueType::Integer),
+            ("wnk", ValueType::Integer),
+            ("lc", ValueType::Integer),
+            ("bedrag", ValueType::Amount),
+        ]);
+        for entry in sorted(data) {
+            table.rows.push(vec![
+                Value::Text(entry.maand.to_string()),
+                Value::Text(entry.kwart.to_string()),
+                Value::Text(privacy.insz(&entry.insz)),
+                Value::Integer(entry.wgc.into()),
+                Value::Integer(entry.wnk.into()),
+                Value::Integer(entry.lc.into()),
+                Value::Amount(entry.brutto_loon),
+            ]);
+        }
+        table
+    }
+
+    /// Writes the table to a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExportError::WriteError` if the file can not be written.
+    pub fn write(&self, filename: &str, format: Format) -> Result<(), ExportError> {
+        let file = File::create(Path::new(filename)).map_err(|_| ExportError::WriteError(filename.to_string()))?;
+        self.write_to(BufWriter::new(file), format).map_err(|_| ExportError::WriteError(filename.to_string()))
+    }
+
+    /// Writes the table to a writer, e.g. stdout.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExportError::WriteError` if writing fails.
+    pub fn write_to<W: Write>(&self, writer: W, format: Format) -> Result<(), ExportError> {
+        match format {
+            Format::Csv => self.write_csv(writer),
+            Format::Ndjson => self.write_ndjson(writer),
+            Format::Json => self.write_json(writer),
+            Format::Parquet => self.write_parquet(writer),
+        }
+    }
+
+    fn write_csv<W: Write>(&self, writer: W) -> Result<(), ExportError> {
+        let error = |e: csv::Error| ExportError::WriteError(e.to_string());
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record(self.columns.iter().map(|(name, _)| name)).map_err(error)?;
+        for row in &self.rows {
+            writer.write_record(row.iter().map(Value::text)).map_err(error)?;
+        }
+        writer.flush().map_err(|e| ExportError::WriteError(e.to_string()))
+    }
+
+    fn write_ndjson<W: Write>(&self, mut writer: W) -> Result<(), ExportError> {
+        let error = |e: std::io::Error| ExportError::WriteError(e.to_string());
+        for row in &self.rows {
+            serde_json::to_writer(&mut writer, &self.object(row)).map_err(|e| ExportError::WriteError(e.to_string()))?;
+            writer.write_all(b"\n").map_err(error)?;
+        }
+        writer.flush().map_err(error)
+    }
+
+    fn write_json<W: Write>(&self, writer: W) -> Result<(), ExportError> {
+        let rows: Vec<JsonValue> = self.rows.iter().map(|row| JsonValue::Object(self.object(row))).collect();
+        serde_json::to_writer_pretty(writer, &rows).map_err(|e| ExportError::WriteError(e.to_string()))
+    }
+
+    fn object(&self, row: &[Value]) -> Map<String, JsonValue> {
+        self.columns.iter().map(|(name, _)| name.to_string()).zip(row.iter().map(Value::json)).collect()
+    }
+
+    fn write_parquet<W: Write>(&self, writer: W) -> Result<(), ExportError> {
+        let columns: Vec<Column> = self.columns.iter().enumerate()
+            .map(|(index, (name, value_type))| {
+                let values = self.rows.iter().map(|row| row.get(index).unwrap_or(&Value::Empty));
+                let series = match value_type {
+                    ValueType::Integer => {
+                        let numbers: Vec<Option<i64>> = values.map(|value| match value {
+                            Value::Integer(number) => Some(*number),
+                            _ => None,
+                        }).collect();
+                        Series::new((*name).into(), numbers)
+                    }
+                    ValueType::Amount => {
+                        let amounts: Vec<Option<f64>> = values.map(|value| match value {
+                            Value::Amount(amount) => Some(amount.to_f64()),
+                            _ => None,
+                        }).collect();
+                        Series::new((*name).into(), amounts)
+                    }
+                    ValueType::Text => {
+                        let texts: Vec<Option<String>> = values.map(|value| match value {
+                            Value::Empty => None,
+                            value => Some(value.text()),
+                        }).collect();
+                        Series::new((*name).into(), texts)
+                    }
+                };
+                series.into()
+            })
+            .collect();
+
+        let mut frame = DataFrame::new(columns).map_err(|e| ExportError::WriteError(e.to_string()))?;
+        ParquetWriter::new(writer).finish(&mut frame).map_err(|e| ExportError::WriteError(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// An Excel serial date (days since 1899-12-30) as an ISO date, e.g. `2022-01-01`.
+fn excel_date(serial: Option<u32>) -> Value {
+    let epoch = Date::from_calendar_date(1899, Month::December, 30).expect("the Excel epoch is a valid date");
+    serial
+        .and_then(|serial| epoch.checked_add(Duration::days(serial.into())))
+        .map_or(Value::Empty, |date| Value::Text(date.to_string()))
+}
+
+/// The entries of a reader, sorted by INSZ and Kwartaal.
+fn sorted<T>(data: &HashMap<Rrn, HashMap<Kwartaal, Vec<T>>>) -> impl Iterator<Item = &T> {
+    let mut persons: Vec<(&Rrn, &HashMap<Kwartaal, Vec<T>>)> = data.iter().collect();
+    persons.sort_by_key(|(insz, _)| *insz);
+    persons.into_iter().flat_map(|(_, kwartalen)| {
+        let mut kwartalen: Vec<(&Kwartaal, &Vec<T>)> = kwartalen.iter().collect();
+        kwartalen.sort_by_key(|(kwart, _)| *kwart);
+        kwartalen.into_iter().flat_map(|(_, entries)| entries)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::{DataType, ParquetReader, SerReader};
+    use tempfile::Builder;
+    use crate::compare::LoonKey;
+
+    fn table() -> Table {
+        let mut table = Table::new(&[("insz", ValueType::Text), ("lc", ValueType::Integer), ("bedrag", ValueType::Amount)]);
+        table.rows.push(vec![Value::Text("69100136359".to_string()), Value::Integer(1), Value::Amount(Money::from_cents(123450))]);
+        table.rows.push(vec![Value::Text("95022899874".to_string()), Value::Empty, Value::Amount(Money::from_cents(-5))]);
+        table
+    }
+
+    fn verschillen() -> Vec<(Verschil, Klasse)> {
+        let verschil = Verschil {
+            key: LoonKey {
+                kwart: Kwartaal::new("20231".to_string()).unwrap(),
+                wgc: 751,
+                wnk: 495,
+                insz: Rrn::new("69100136359").unwrap(),
+                lc: 1,
+            },
+            loonbestand: Money::from_cents(567699),
+            dmfa: Money::from_cents(478642),
+        };
+        vec![(verschil, Klasse::Materieel)]
+    }
+
+    #[test]
+    fn test_table_csv() {
+        let mut csv = Vec::new();
+        table().write_to(&mut csv, Format::Csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "insz,lc,bedrag\n69100136359,1,1234.50\n95022899874,,-0.05\n");
+    }
+
+    #[test]
+    fn test_table_ndjson() {
+        let mut ndjson = Vec::new();
+        table().write_to(&mut ndjson, Format::Ndjson).unwrap();
+        let ndjson = String::from_utf8(ndjson).unwrap();
+        let lines: Vec<JsonValue> = ndjson.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["bedrag"], 1234.5);
+        assert_eq!(lines[1]["lc"], JsonValue::Null);
+    }
+
+    #[test]
+    fn test_table_json() {
+        let mut json = Vec::new();
+        table().write_to(&mut json, Format::Json).unwrap();
+        let json: JsonValue = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json[0]["insz"], "69100136359");
+        assert_eq!(json[1]["bedrag"], -0.05);
+    }
+
+    #[test]
+    fn test_table_parquet() {
+        let file = Builder::new().suffix(".parquet").tempfile().unwrap();
+        table().write(file.path().to_str().unwrap(), Format::Parquet).unwrap();
+
+        let frame = ParquetReader::new(File::open(file.path()).unwrap()).finish().unwrap();
+        assert_eq!(frame.shape(), (2, 3));
+        assert_eq!(frame.column("lc").unwrap().i64().unwrap().get(1), None);
+        assert_eq!(frame.column("bedrag").unwrap().f64().unwrap().get(0), Some(1234.5));
+    }
+
+    #[test]
+    fn test_table_parquet_schema_without_rows() {
+        // The types come from the schema, not from the values
+        let file = Builder::new().suffix(".parquet").tempfile().unwrap();
+        Table::verschillen(&[], &Privacy::default()).write(file.path().to_str().unwrap(), Format::Parquet).unwrap();
+
+        let frame = ParquetReader::new(File::open(file.path()).unwrap()).finish().unwrap();
+        let schema: Vec<(String, DataType)> = frame.get_columns().iter()
+            .map(|column| (column.name().to_string(), column.dtype().clone()))
+            .collect();
+        assert_eq!(schema[0], ("kwartaal".to_string(), DataType::String));
+        assert_eq!(schema[4], ("lc".to_string(), DataType::Int64));
+        assert_eq!(schema[7], ("verschil".to_string(), DataType::Float64));
+    }
+
+    #[test]
+    fn test_table_verschillen() {
+        let privacy = Privacy::new(PrivacyProfile::Masked, b"").unwrap();
+        let table = Table::verschillen(&verschillen(), &privacy);
+        let names: Vec<&str> = table.columns.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, ["kwartaal", "wgc", "wnk", "insz", "lc", "loonbestand", "dmfa", "verschil", "klasse"]);
+        assert_eq!(table.rows[0][3], Value::Text("69.10.01-***.**".to_string()));
+        assert_eq!(table.rows[0][7], Value::Amount(Money::from_cents(89057)));
+        assert_eq!(table.rows[0][8], Value::Text("materieel".to_string()));
+    }
+
+    #[test]
+    fn test_table_dmfa() {
+        let data = crate::DmfaReader::new("tests/fixtures/207527540-dmfa.xlsx").unwrap().data().unwrap();
+        let table = Table::dmfa(&data, &Privacy::default());
+        let lines: usize = data.values().flat_map(|kwartalen| kwartalen.values()).map(Vec::len).sum();
+        assert_eq!(table.rows.len(), lines);
+        assert!(table.rows.windows(2).all(|rows| rows[0][1].text() <= rows[1][1].text()));
+        assert_eq!(table.rows[0][5], Value::Text("2022-01-01".to_string()));
+        assert!(table.rows.iter().all(|row| row.len() == table.columns.len()));
+    }
+
+    #[test]
+    fn test_table_cipal_privacy() {
+        let data = crate::CipalReader::new("tests/fixtures/207527540-cipal.xlsx").unwrap().data().unwrap();
+        let privacy = Privacy::new(PrivacyProfile::Tokenised, b"geheim").unwrap();
+        let table = Table::cipal(&data[..10], &privacy);
+        assert!(table.rows.iter().all(|row| row[3] == Value::Empty && row[4] == Value::Empty));
+        assert_eq!(table.rows[0][2], Value::Text(privacy.token(&data[0].insz)));
+
+        let table = Table::cipal(&data[..10], &Privacy::new(PrivacyProfile::Clear, b"").unwrap());
+        assert_eq!(table.rows[2][3], Value::Text("AMTER".to_string()));
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!("Parquet".parse::<Format>().unwrap(), Format::Parquet);
+        assert_eq!("jsonl".parse::<Format>().unwrap(), Format::Ndjson);
+        assert_eq!("xml".parse::<Format>().unwrap_err(), ExportError::InvalidFormat("xml".to_string()));
+        assert_eq!(Format::from_path("uit/verschil.ndjson").unwrap(), Format::Ndjson);
+        assert_eq!(Format::from_path("verschil").unwrap_err(), ExportError::InvalidFormat("verschil".to_string()));
+    }
+}
@@ -0,0 +1,173 @@
+// The DMFA export has two header rows, Dutch then French (Kwart/Trim, KBO/BCE, INSZ/NISS, RSZnr/NOSS, ...).
+// The columns are found by exact name in either row, so an export with only a trustworthy French row loads too.
+// The other formats are resolved the same way, from the aliases of their schema (see `schema`).
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// A column of the DMFA export that is read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DmfaKolom {
+    Kwart,
+    RszNr,
+    Kbo,
+    Wgc,
+    Wnk,
+    Insz,
+    TNr,
+    TBegin,
+    TEind,
+    Lc,
+    LcBedr,
+}
+
+impl DmfaKolom {
+    pub const ALLE: [DmfaKolom; 11] = [
+        DmfaKolom::Kwart,
+        DmfaKolom::RszNr,
+        DmfaKolom::Kbo,
+        DmfaKolom::Wgc,
+        DmfaKolom::Wnk,
+        DmfaKolom::Insz,
+        DmfaKolom::TNr,
+        DmfaKolom::TBegin,
+        DmfaKolom::TEind,
+        DmfaKolom::Lc,
+        DmfaKolom::LcBedr,
+    ];
+
+    /// The canonical name, the Dutch header of the column.
+    pub const fn naam(&self) -> &'static str {
+        match self {
+            DmfaKolom::Kwart => "Kwart",
+            DmfaKolom::RszNr => "RSZnr",
+            DmfaKolom::Kbo => "KBO",
+            DmfaKolom::Wgc => "WGC",
+            DmfaKolom::Wnk => "WNK",
+            DmfaKolom::Insz => "INSZ",
+            DmfaKolom::TNr => "T_nr",
+            DmfaKolom::TBegin => "T_begin",
+            DmfaKolom::TEind => "T_eind",
+            DmfaKolom::Lc => "LC",
+            DmfaKolom::LcBedr => "LC_bedr",
+        }
+    }
+
+    /// The Dutch and French headers of the column, lowercase and without accents.
+    pub const fn aliassen(&self) -> &'static [&'static str] {
+        match self {
+            DmfaKolom::Kwart => &["kwart", "kwartaal", "trim", "trimestre"],
+            DmfaKolom::RszNr => &["rsznr", "noss"],
+            DmfaKolom::Kbo => &["kbo", "bce"],
+            DmfaKolom::Wgc => &["wgc", "cat_empl"],
+            DmfaKolom::Wnk => &["wnk", "code_trav"],
+            DmfaKolom::Insz => &["insz", "niss"],
+            DmfaKolom::TNr => &["t_nr", "nbr_occup"],
+            DmfaKolom::TBegin => &["t_begin", "occ_debut"],
+            DmfaKolom::TEind => &["t_eind", "occ_fin"],
+            DmfaKolom::Lc => &["lc", "code_rem"],
+            DmfaKolom::LcBedr => &["lc_bedr", "rem"],
+        }
+    }
+
+    /// The column a header belongs to, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lvgl::DmfaKolom;
+    ///
+    /// assert_eq!(DmfaKolom::from_header(" Code_rém "), Some(DmfaKolom::Lc));
+    /// assert_eq!(DmfaKolom::from_header("LC_bedr"), Some(DmfaKolom::LcBedr));
+    /// assert_eq!(DmfaKolom::from_header("WNK_b"), None);
+    /// ```
+    pub fn from_header(header: &str) -> Option<DmfaKolom> {
+        let header = normaliseer(header);
+        DmfaKolom::ALLE.into_iter().find(|kolom| kolom.aliassen().contains(&header.as_str()))
+    }
+}
+
+impl fmt::Display for DmfaKolom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.naam())
+    }
+}
+
+/// The columns of a document, by canonical name, resolved from its header rows with `Schema::zoek`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Kolommen {
+    pub(crate) kolommen: HashMap<&'static str, u32>,
+}
+
+impl Kolommen {
+    /// The 1-based column of the canonical name `naam`, if the header has it.
+    pub fn get(&self, naam: &str) -> Option<u32> {
+        self.kolommen.get(naam).copied()
+    }
+
+    /// Whether the header has all the columns.
+    pub fn has(&self, namen: &[&str]) -> bool {
+        namen.iter().all(|naam| self.kolommen.contains_key(naam))
+    }
+}
+
+/// Trims and lowercases a header, and drops the accents of the French headers.
+pub(crate) fn normaliseer(header: &str) -> String {
+    header.trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'à' | 'â' | 'ä' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'î' | 'ï' => 'i',
+            'ô' | 'ö' => 'o',
+            'ù' | 'û' | 'ü' => 'u',
+            'ç' => 'c',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::DMFA;
+    use crate::xlsx::{Row, XlsxReader};
+
+    fn header(path: &str) -> Vec<Row> {
+        let mut workbook = XlsxReader::open(path).unwrap();
+        workbook.sheet(0).unwrap().take(2).collect::<Result<_, _>>().unwrap()
+    }
+
+    #[test]
+    fn test_from_header() {
+        assert_eq!(DmfaKolom::from_header("Kwart"), Some(DmfaKolom::Kwart));
+        assert_eq!(DmfaKolom::from_header("TRIM"), Some(DmfaKolom::Kwart));
+        assert_eq!(DmfaKolom::from_header("Occ_début"), Some(DmfaKolom::TBegin));
+        assert_eq!(DmfaKolom::from_header("Rém"), Some(DmfaKolom::LcBedr));
+        // Exact matching : no more WNK for WNK_b, or LC for MRA_OC
+        assert_eq!(DmfaKolom::from_header("WNK_b"), None);
+        assert_eq!(DmfaKolom::from_header("Rém_pct"), None);
+        assert_eq!(DmfaKolom::from_header("MRA_OC"), None);
+        assert_eq!(DmfaKolom::from_header(""), None);
+    }
+
+    #[test]
+    fn test_kolommen() {
+        let rows = header("tests/fixtures/207527540-dmfa.xlsx");
+        let beide = DMFA.zoek(&rows);
+        let nederlands = DMFA.zoek(&rows[..1]);
+        let frans = DMFA.zoek(&rows[1..]);
+
+        assert!(beide.has(&DmfaKolom::ALLE.map(|kolom| kolom.naam())));
+        assert_eq!(nederlands, beide);
+        assert_eq!(frans, beide);
+        assert_eq!(beide.get("KBO"), Some(3));
+        assert_eq!(beide.get(DmfaKolom::Wnk.naam()), Some(6));
+        assert_eq!(beide.get("LC"), Some(18));
+        assert_eq!(beide.get("LC_bedr"), Some(19));
+
+        assert_eq!(DMFA.zoek(&[]).get("Kwart"), None);
+        assert!(!DMFA.zoek(&[]).has(&["Kwart"]));
+    }
+}
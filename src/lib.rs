@@ -2,5 +2,53 @@
 pub mod types;
 pub use types::*;
 
+pub mod kolommen;
+pub use kolommen::{DmfaKolom, Kolommen};
+
+pub mod diagnose;
+pub use diagnose::{highlight, CellError, DiagnoseError};
+
+pub mod schema;
+pub use schema::{schema, KolomSchema, Kolomtype, Probleem, Schema, SchemaError, Verplicht, BOSA, CIPAL, DMFA, SCHEMAS, VERSCHIL};
+
 pub mod dmfa;
-pub use dmfa::{DmfaReader, DmfaEntry, DmfaError};
+pub use dmfa::{DmfaReader, DmfaData, DmfaEntry, DmfaError};
+
+pub mod cipal;
+pub use cipal::{CipalReader, CipalEntry, CipalInfo, CipalError};
+
+pub mod bosa;
+pub use bosa::{BosaReader, BosaData, BosaEntry, BosaError};
+
+pub mod mapping;
+pub use mapping::{Mapping, MappingError, NietGemapt};
+
+pub mod codes;
+pub use codes::{Code, Codefout, Codes, CodesError, Codesoort};
+
+pub mod compare;
+pub use compare::{compare_dmfa_bosa, compare_dmfa_cipal, totalen_per_periode, CompareError, LoonKey, LoonTotalen, Verschil, Vergelijking};
+
+pub mod privacy;
+pub use privacy::{Privacy, PrivacyError, PrivacyProfile};
+
+pub mod report;
+pub use report::{VerschilReport, ReportError};
+
+pub mod tolerantie;
+pub use tolerantie::{Klasse, LooncodeTolerantie, Tolerantie, TolerantieError};
+
+pub mod export;
+pub use export::{ExportError, Format, Table, Value, ValueType};
+
+pub mod xlsx;
+pub use xlsx::{cell_reference, Row, Rows, XlsxError, XlsxReader};
+
+pub mod batch;
+pub use batch::{Batch, BatchError, BatchResult, Employer, Outcome, Scan};
+
+pub mod bron;
+pub use bron::{detect, file_type, Bron, BronError, FileType};
+
+#[cfg(test)]
+mod test_util;
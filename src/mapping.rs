@@ -0,0 +1,403 @@
+// CIPAL bezoldigingscode → DMFA looncode
+// CIPAL wage lines carry a bezoldigingscode, a CIPAL code and an aard, while the DMFA only knows the looncode (LC).
+// The mapping between both is a list of exclusions and rules, loaded from TOML or CSV.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Deserializer};
+use thiserror::Error;
+use crate::cipal::CipalEntry;
+use crate::types::Money;
+
+/// The default mapping, reproducing the verschil documents of the accountants.
+pub const DEFAULT_MAPPING: &str = include_str!("../config/cipal-looncodes.toml");
+
+#[derive(Error, Debug, PartialEq)]
+pub enum MappingError {
+    #[error("Invalid filename.")]
+    InvalidFilename,
+    #[error("File not found.")]
+    FileNotFound,
+    #[error("Invalid file extension.")]
+    InvalidExtension,
+    #[error("Invalid TOML mapping : {0}")]
+    Toml(String),
+    #[error("Invalid CSV mapping : {0}")]
+    Csv(String),
+    #[error("Invalid soort {0}, expected regel or uitsluiting.")]
+    InvalidSoort(String),
+    #[error("Invalid period {0}, expected YYYYMM.")]
+    InvalidPeriod(u32),
+}
+
+/// Selects CIPAL wage lines. An empty list matches every value, all fields have to match.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Selectie {
+    #[serde(default, deserialize_with = "een_of_meer")]
+    pub bezoldigingscode: Vec<u16>,
+    #[serde(default, deserialize_with = "een_of_meer")]
+    pub code: Vec<u16>,
+    #[serde(default, deserialize_with = "een_of_meer")]
+    pub aard: Vec<u16>,
+    #[serde(default, deserialize_with = "een_of_meer")]
+    pub wgc: Vec<u16>,
+    #[serde(default, deserialize_with = "een_of_meer")]
+    pub wnk: Vec<u16>,
+    pub van: Option<u32>,       // Eerste maand (YYYYMM) waarin de selectie geldt
+    pub tot: Option<u32>,       // Laatste maand (YYYYMM) waarin de selectie geldt
+}
+
+impl Selectie {
+    /// Checks if the CIPAL wage line is selected.
+    pub fn matches(&self, entry: &CipalEntry) -> bool {
+        let maand = entry.maand.year as u32 * 100 + entry.maand.month as u32;
+
+        bevat(&self.bezoldigingscode, entry.bezoldigingscode)
+            && bevat(&self.code, entry.code)
+            && bevat(&self.aard, entry.aard)
+            && bevat(&self.wgc, entry.wgc)
+            && bevat(&self.wnk, entry.wnk)
+            && self.van.is_none_or(|van| maand >= van)
+            && self.tot.is_none_or(|tot| maand <= tot)
+    }
+
+    fn validate(&self) -> Result<(), MappingError> {
+        for periode in self.van.iter().chain(self.tot.iter()) {
+            if !(197001..=210012).contains(periode) || !(1..=12).contains(&(periode % 100)) {
+                return Err(MappingError::InvalidPeriod(*periode));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Maps the selected CIPAL wage lines to a DMFA looncode. Without `lc` the bezoldigingscode is the looncode.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Regel {
+    #[serde(flatten)]
+    pub selectie: Selectie,
+    pub lc: Option<u16>,
+    pub omschrijving: Option<String>,
+}
+
+/// The selected CIPAL wage lines are not declared in the DMFA.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Uitsluiting {
+    #[serde(flatten)]
+    pub selectie: Selectie,
+    pub omschrijving: Option<String>,
+}
+
+/// What happens with a CIPAL wage line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Toewijzing {
+    Looncode(u16),
+    Uitgesloten,
+    NietGemapt,
+}
+
+/// The CIPAL wage lines of one code that are neither excluded nor mapped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NietGemapt {
+    pub bezoldigingscode: u16,
+    pub code: u16,
+    pub aard: u16,
+    pub omschrijving: String,   // korte omschrijving looncode
+    pub lijnen: usize,          // Aantal lijnen
+    pub brutobedrag: Money,     // Som van de brutobedragen
+}
+
+/// The mapping from CIPAL wage lines to DMFA looncodes.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Mapping {
+    #[serde(default, rename = "uitsluiting")]
+    pub uitsluitingen: Vec<Uitsluiting>,
+    #[serde(default, rename = "regel")]
+    pub regels: Vec<Regel>,
+}
+
+#[derive(Deserialize)]
+struct CsvRegel {
+    soort: String,
+    #[serde(default)]
+    bezoldigingscode: String,
+    #[serde(default)]
+    code: String,
+    #[serde(default)]
+    aard: String,
+    #[serde(default)]
+    wgc: String,
+    #[serde(default)]
+    wnk: String,
+    van: Option<u32>,
+    tot: Option<u32>,
+    lc: Option<u16>,
+    omschrijving: Option<String>,
+}
+
+impl Mapping {
+    /// The embedded default mapping (config/cipal-looncodes.toml).
+    pub fn standaard() -> Self {
+        Mapping::from_toml(DEFAULT_MAPPING).expect("the embedded mapping is valid")
+    }
+
+    /// Loads a mapping from a TOML or CSV file, depending on the extension.
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - A string slice that holds the filename of the mapping.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MappingError` if the filename is invalid, the file is not found, the extension is not
+    /// .toml or .csv, or the content is not a valid mapping.
+    pub fn load(filename: &str) -> Result<Self, MappingError> {
+        if filename.is_empty() {
+            return Err(MappingError::InvalidFilename);
+        }
+
+        let extension = Path::new(filename).extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+        if !matches!(extension.as_deref(), Some("toml") | Some("csv")) {
+            return Err(MappingError::InvalidExtension);
+        }
+
+        let content = fs::read_to_string(filename).map_err(|_| MappingError::FileNotFound)?;
+
+        match extension.as_deref() {
+            Some("csv") => Mapping::from_csv(&content),
+            _ => Mapping::from_toml(&content),
+        }
+    }
+
+    /// Parses a mapping with `[[uitsluiting]]` and `[[regel]]` tables.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lvgl::mapping::Mapping;
+    ///
+    /// let mapping = Mapping::from_toml(r#"
+    ///     [[uitsluiting]]
+    ///     bezoldigingscode = [24, 46]
+    ///
+    ///     [[regel]]
+    ///     bezoldigingscode = [998, 999]
+    ///     lc = 51
+    ///     van = 202301
+    /// "#).unwrap();
+    /// assert_eq!(mapping.regels[0].lc, Some(51));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `MappingError::Toml` if the content can not be parsed, or `MappingError::InvalidPeriod`.
+    pub fn from_toml(content: &str) -> Result<Self, MappingError> {
+        let mapping: Mapping = toml::from_str(content).map_err(|e| MappingError::Toml(e.message().to_string()))?;
+        mapping.validate()?;
+        Ok(mapping)
+    }
+
+    /// Parses a mapping with the columns soort (regel or uitsluiting), bezoldigingscode, code, aard, wgc,
+    /// wnk, van, tot, lc and omschrijving. Multiple codes in one cell are separated by `|`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MappingError::Csv` if the content can not be parsed, `MappingError::InvalidSoort` or
+    /// `MappingError::InvalidPeriod`.
+    pub fn from_csv(content: &str) -> Result<Self, MappingError> {
+        let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(content.as_bytes());
+        let mut mapping = Mapping::default();
+
+        for record in reader.deserialize::<CsvRegel>() {
+            let record = record.map_err(|e| MappingError::Csv(e.to_string()))?;
+            let selectie = Selectie {
+                bezoldigingscode: csv_codes(&record.bezoldigingscode)?,
+                code: csv_codes(&record.code)?,
+                aard: csv_codes(&record.aard)?,
+                wgc: csv_codes(&record.wgc)?,
+                wnk: csv_codes(&record.wnk)?,
+                van: record.van,
+                tot: record.tot,
+            };
+            let omschrijving = record.omschrijving.filter(|omschrijving| !omschrijving.is_empty());
+
+            match record.soort.to_lowercase().as_str() {
+                "regel" => mapping.regels.push(Regel { selectie, lc: record.lc, omschrijving }),
+                "uitsluiting" => mapping.uitsluitingen.push(Uitsluiting { selectie, omschrijving }),
+                _ => return Err(MappingError::InvalidSoort(record.soort)),
+            }
+        }
+
+        mapping.validate()?;
+        Ok(mapping)
+    }
+
+    /// Decides what happens with a CIPAL wage line : the exclusions are checked first, then the rules.
+    pub fn toewijzing(&self, entry: &CipalEntry) -> Toewijzing {
+        if self.uitsluitingen.iter().any(|uitsluiting| uitsluiting.selectie.matches(entry)) {
+            return Toewijzing::Uitgesloten;
+        }
+
+        match self.regels.iter().find(|regel| regel.selectie.matches(entry)) {
+            Some(regel) => Toewijzing::Looncode(regel.lc.unwrap_or(entry.bezoldigingscode)),
+            None => Toewijzing::NietGemapt,
+        }
+    }
+
+    /// Collects the CIPAL wage lines that are neither excluded nor mapped, per bezoldigingscode, code and aard.
+    pub fn niet_gemapt(&self, entries: &[CipalEntry]) -> Vec<NietGemapt> {
+        let mut niet_gemapt: BTreeMap<(u16, u16, u16), NietGemapt> = BTreeMap::new();
+
+        for entry in entries.iter().filter(|entry| self.toewijzing(entry) == Toewijzing::NietGemapt) {
+            let lijn = niet_gemapt.entry((entry.bezoldigingscode, entry.code, entry.aard))
+                .or_insert_with(|| NietGemapt {
+                    bezoldigingscode: entry.bezoldigingscode,
+                    code: entry.code,
+                    aard: entry.aard,
+                    omschrijving: entry.omschrijving.clone(),
+                    lijnen: 0,
+                    brutobedrag: Money::ZERO,
+                });
+            lijn.lijnen += 1;
+            lijn.brutobedrag += entry.brutobedrag;
+        }
+
+        niet_gemapt.into_values().collect()
+    }
+
+    fn validate(&self) -> Result<(), MappingError> {
+        self.uitsluitingen.iter().map(|uitsluiting| &uitsluiting.selectie)
+            .chain(self.regels.iter().map(|regel| &regel.selectie))
+            .try_for_each(Selectie::validate)
+    }
+}
+
+fn bevat(codes: &[u16], code: u16) -> bool {
+    codes.is_empty() || codes.contains(&code)
+}
+
+fn csv_codes(value: &str) -> Result<Vec<u16>, MappingError> {
+    value.split('|')
+        .map(str::trim)
+        .filter(|code| !code.is_empty())
+        .map(|code| code.parse().map_err(|_| MappingError::Csv(format!("invalid code {code}"))))
+        .collect()
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EenOfMeer {
+    Een(u16),
+    Meer(Vec<u16>),
+}
+
+fn een_of_meer<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u16>, D::Error> {
+    Ok(match EenOfMeer::deserialize(deserializer)? {
+        EenOfMeer::Een(code) => vec![code],
+        EenOfMeer::Meer(codes) => codes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CipalMonth, Rrn};
+
+    fn entry(maand: &str, wgc: u16, wnk: u16, bezoldigingscode: u16) -> CipalEntry {
+        CipalEntry {
+            naam: "Naam".to_string(),
+            voornaam: "Voornaam".to_string(),
+            insz: Rrn::new("50062049792").unwrap(),
+            persnr: 1,
+            maand: CipalMonth::new(maand.to_string()).unwrap(),
+            wgc,
+            wnk,
+            aard: 11,
+            code: 101,
+            extensie: 0,
+            bezoldigingscode,
+            omschrijving: "LOON".to_string(),
+            kostenplaats: 0,
+            brutobedrag: Money::from_cents(10000),
+            patr_rsz: Money::ZERO,
+            patr_pens: Money::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_mapping_standaard() {
+        let mapping = Mapping::standaard();
+        assert_eq!(mapping.toewijzing(&entry("01/2023", 751, 495, 1)), Toewijzing::Looncode(1));
+        assert_eq!(mapping.toewijzing(&entry("01/2023", 751, 495, 2)), Toewijzing::Looncode(2));
+        assert_eq!(mapping.toewijzing(&entry("01/2023", 751, 675, 2)), Toewijzing::Looncode(62));
+        assert_eq!(mapping.toewijzing(&entry("01/2023", 751, 495, 999)), Toewijzing::Looncode(51));
+        assert_eq!(mapping.toewijzing(&entry("01/2023", 0, 0, 1)), Toewijzing::Uitgesloten);
+        assert_eq!(mapping.toewijzing(&entry("01/2023", 751, 495, 46)), Toewijzing::Uitgesloten);
+        assert_eq!(mapping.toewijzing(&entry("01/2023", 751, 841, 1)), Toewijzing::Uitgesloten);
+        assert_eq!(mapping.toewijzing(&entry("01/2023", 751, 495, 7)), Toewijzing::NietGemapt);
+    }
+
+    #[test]
+    fn test_mapping_many_to_one_and_validity() {
+        let mapping = Mapping::from_toml(r#"
+            [[regel]]
+            bezoldigingscode = [998, 999]
+            lc = 51
+            van = 202301
+            tot = 202306
+
+            [[regel]]
+            bezoldigingscode = 999
+            lc = 52
+            van = 202307
+        "#).unwrap();
+        assert_eq!(mapping.toewijzing(&entry("03/2023", 751, 495, 998)), Toewijzing::Looncode(51));
+        assert_eq!(mapping.toewijzing(&entry("06/2023", 751, 495, 999)), Toewijzing::Looncode(51));
+        assert_eq!(mapping.toewijzing(&entry("07/2023", 751, 495, 999)), Toewijzing::Looncode(52));
+        assert_eq!(mapping.toewijzing(&entry("07/2023", 751, 495, 998)), Toewijzing::NietGemapt);
+        assert_eq!(mapping.toewijzing(&entry("12/2022", 751, 495, 999)), Toewijzing::NietGemapt);
+    }
+
+    #[test]
+    fn test_mapping_from_csv() {
+        let mapping = Mapping::from_csv("\
+soort,bezoldigingscode,code,aard,wgc,wnk,van,tot,lc,omschrijving
+uitsluiting,24|46,,,,,,,,Voordelen
+regel,2,,,,675,,,62,
+regel,998|999,,,,,202301,,51,DISPO
+").unwrap();
+        assert_eq!(mapping.uitsluitingen.len(), 1);
+        assert_eq!(mapping.uitsluitingen[0].selectie.bezoldigingscode, vec![24, 46]);
+        assert_eq!(mapping.regels[0].omschrijving, None);
+        assert_eq!(mapping.toewijzing(&entry("01/2023", 751, 675, 2)), Toewijzing::Looncode(62));
+        assert_eq!(mapping.toewijzing(&entry("01/2023", 751, 495, 998)), Toewijzing::Looncode(51));
+        assert_eq!(mapping.toewijzing(&entry("01/2023", 751, 495, 24)), Toewijzing::Uitgesloten);
+    }
+
+    #[test]
+    fn test_mapping_invalid() {
+        assert_eq!(Mapping::from_toml("[[regel]]\nvan = 202313\n").unwrap_err(), MappingError::InvalidPeriod(202313));
+        assert_eq!(Mapping::from_csv("soort,lc\nregels,1\n").unwrap_err(), MappingError::InvalidSoort("regels".to_string()));
+        assert!(matches!(Mapping::from_toml("[[regel]]\nlc = \"een\"\n").unwrap_err(), MappingError::Toml(_)));
+        assert_eq!(Mapping::load("mapping.json").unwrap_err(), MappingError::InvalidExtension);
+        assert_eq!(Mapping::load("nonexistent.toml").unwrap_err(), MappingError::FileNotFound);
+    }
+
+    #[test]
+    fn test_mapping_niet_gemapt() {
+        let mapping = Mapping::standaard();
+        let entries = vec![
+            entry("01/2023", 751, 495, 1),
+            entry("01/2023", 751, 495, 7),
+            entry("02/2023", 751, 495, 7),
+        ];
+        let niet_gemapt = mapping.niet_gemapt(&entries);
+        assert_eq!(niet_gemapt.len(), 1);
+        assert_eq!(niet_gemapt[0].bezoldigingscode, 7);
+        assert_eq!(niet_gemapt[0].lijnen, 2);
+        assert_eq!(niet_gemapt[0].brutobedrag, Money::from_cents(20000));
+    }
+}
@@ -0,0 +1,202 @@
+// Pseudonymisation of the INSZ numbers in the reports.
+// The comparison itself always joins on the clear INSZ, the privacy profile is only applied when writing output,
+// so the same person gets the same token in every source and every report made with the same key.
+
+use std::fs::File;
+use std::path::Path;
+use std::str::FromStr;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use thiserror::Error;
+use crate::types::Rrn;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum PrivacyError {
+    #[error("Invalid privacy profile {0}, expected clear, masked or tokenised.")]
+    InvalidProfile(String),
+    #[error("A key is needed to tokenise INSZ numbers.")]
+    MissingKey,
+    #[error("Could not write the token table {0}.")]
+    WriteError(String),
+}
+
+/// How INSZ numbers appear in the output, masked unless clear text is asked for explicitly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PrivacyProfile {
+    /// The INSZ as is, e.g. `69100136359`.
+    Clear,
+    /// Only the birth date is kept, e.g. `69.10.01-***.**`.
+    #[default]
+    Masked,
+    /// A keyed HMAC token, e.g. `P3F1A0C7D92B4E6A1`.
+    Tokenised,
+}
+
+impl FromStr for PrivacyProfile {
+    type Err = PrivacyError;
+
+    fn from_str(profile: &str) -> Result<Self, Self::Err> {
+        match profile.trim().to_lowercase().as_str() {
+            "clear" => Ok(PrivacyProfile::Clear),
+            "masked" => Ok(PrivacyProfile::Masked),
+            "tokenised" | "tokenized" => Ok(PrivacyProfile::Tokenised),
+            _ => Err(PrivacyError::InvalidProfile(profile.to_string())),
+        }
+    }
+}
+
+/// Renders INSZ numbers according to a `PrivacyProfile`.
+#[derive(Clone, Default)]
+pub struct Privacy {
+    pub profile: PrivacyProfile,
+    key: Vec<u8>,
+}
+
+impl std::fmt::Debug for Privacy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print the key
+        f.debug_struct("Privacy").field("profile", &self.profile).finish_non_exhaustive()
+    }
+}
+
+impl Privacy {
+    /// Creates a new `Privacy` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - The privacy profile.
+    /// * `key` - The secret key of the HMAC tokens, only needed for `PrivacyProfile::Tokenised`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PrivacyError::MissingKey` if the profile is tokenised and the key is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lvgl::{Privacy, PrivacyProfile, Rrn};
+    ///
+    /// let rrn = Rrn::new("69.10.01-363.59").unwrap();
+    /// let privacy = Privacy::new(PrivacyProfile::Masked, b"").unwrap();
+    /// assert_eq!(privacy.insz(&rrn), "69.10.01-***.**");
+    /// ```
+    pub fn new(profile: PrivacyProfile, key: &[u8]) -> Result<Self, PrivacyError> {
+        if profile == PrivacyProfile::Tokenised && key.is_empty() {
+            return Err(PrivacyError::MissingKey);
+        }
+
+        Ok(Privacy { profile, key: key.to_vec() })
+    }
+
+    /// The INSZ as it has to appear in the output.
+    pub fn insz(&self, rrn: &Rrn) -> String {
+        match self.profile {
+            PrivacyProfile::Clear => rrn.to_string(),
+            PrivacyProfile::Masked => mask(rrn),
+            PrivacyProfile::Tokenised => self.token(rrn),
+        }
+    }
+
+    /// The keyed HMAC-SHA256 token of the INSZ : `P` followed by the first 16 hexadecimal digits.
+    pub fn token(&self, rrn: &Rrn) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(rrn.to_string().as_bytes());
+        let digest = mac.finalize().into_bytes();
+        format!("P{}", hex::encode_upper(&digest[..8]))
+    }
+
+    /// Writes the token table (token, INSZ) to a CSV file, to be stored apart from the reports so the
+    /// tokens can be traced back to the people by those who are allowed to.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PrivacyError::WriteError` if the file can not be written.
+    pub fn write_token_table<'a>(&self, filename: &str, rrns: impl IntoIterator<Item = &'a Rrn>) -> Result<(), PrivacyError> {
+        let error = || PrivacyError::WriteError(filename.to_string());
+        let file = File::create(Path::new(filename)).map_err(|_| error())?;
+        let mut writer = csv::Writer::from_writer(file);
+
+        writer.write_record(["token", "insz"]).map_err(|_| error())?;
+        let mut rrns: Vec<&Rrn> = rrns.into_iter().collect();
+        rrns.sort();
+        rrns.dedup();
+        for rrn in rrns {
+            writer.write_record([self.token(rrn), rrn.to_string()]).map_err(|_| error())?;
+        }
+        writer.flush().map_err(|_| error())
+    }
+}
+
+/// Keeps the birth date part of the INSZ, e.g. `69.10.01-***.**`.
+fn mask(rrn: &Rrn) -> String {
+    let rrn = rrn.to_string();
+    format!("{}.{}.{}-***.**", &rrn[..2], &rrn[2..4], &rrn[4..6])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::Builder;
+
+    #[test]
+    fn test_privacy_clear() {
+        let rrn = Rrn::new("69.10.01-363.59").unwrap();
+        let privacy = Privacy::new(PrivacyProfile::Clear, b"").unwrap();
+        assert_eq!(privacy.insz(&rrn), "69100136359");
+    }
+
+    #[test]
+    fn test_privacy_default_masked() {
+        let rrn = Rrn::new("69.10.01-363.59").unwrap();
+        assert_eq!(Privacy::default().insz(&rrn), "69.10.01-***.**");
+        assert_eq!(PrivacyProfile::default(), PrivacyProfile::Masked);
+    }
+
+    #[test]
+    fn test_privacy_masked() {
+        let rrn = Rrn::new("11820963").unwrap();
+        let privacy = Privacy::new(PrivacyProfile::Masked, b"").unwrap();
+        assert_eq!(privacy.insz(&rrn), "00.01.18-***.**");
+    }
+
+    #[test]
+    fn test_privacy_tokenised() {
+        let privacy = Privacy::new(PrivacyProfile::Tokenised, b"geheim").unwrap();
+        let token = privacy.insz(&Rrn::new("69.10.01-363.59").unwrap());
+        assert_eq!(token.len(), 17);
+        assert!(token.starts_with('P'));
+
+        // The same person gets the same token, whatever the notation in the source
+        assert_eq!(token, privacy.insz(&Rrn::new("69100136359").unwrap()));
+        assert_ne!(token, privacy.insz(&Rrn::new("95022899874").unwrap()));
+
+        // ... but another key gives another token
+        let other = Privacy::new(PrivacyProfile::Tokenised, b"anders").unwrap();
+        assert_ne!(token, other.insz(&Rrn::new("69100136359").unwrap()));
+    }
+
+    #[test]
+    fn test_privacy_missing_key() {
+        assert_eq!(Privacy::new(PrivacyProfile::Tokenised, b"").unwrap_err(), PrivacyError::MissingKey);
+    }
+
+    #[test]
+    fn test_privacy_profile_from_str() {
+        assert_eq!("Masked".parse::<PrivacyProfile>().unwrap(), PrivacyProfile::Masked);
+        assert_eq!("tokenized".parse::<PrivacyProfile>().unwrap(), PrivacyProfile::Tokenised);
+        assert_eq!("open".parse::<PrivacyProfile>().unwrap_err(), PrivacyError::InvalidProfile("open".to_string()));
+    }
+
+    #[test]
+    fn test_privacy_token_table() {
+        let privacy = Privacy::new(PrivacyProfile::Tokenised, b"geheim").unwrap();
+        let rrns = [Rrn::new("95022899874").unwrap(), Rrn::new("69100136359").unwrap(), Rrn::new("95022899874").unwrap()];
+        let file = Builder::new().suffix(".csv").tempfile().unwrap();
+        privacy.write_token_table(file.path().to_str().unwrap(), &rrns).unwrap();
+
+        let table = std::fs::read_to_string(file.path()).unwrap();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], format!("{},69100136359", privacy.token(&rrns[1])));
+    }
+}
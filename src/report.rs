@@ -0,0 +1,371 @@
+// Het "verschil" document : the difference table in the layout the accountants expect
+// (see tests/fixtures/207527540-verschil.xlsx).
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use umya_spreadsheet::{NumberingFormat, Spreadsheet, Worksheet};
+use thiserror::Error;
+use crate::codes::{Codes, Codesoort};
+use crate::compare::{LoonKey, Verschil};
+use crate::privacy::Privacy;
+use crate::types::Money;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ReportError {
+    #[error("Invalid filename.")]
+    InvalidFilename,
+    #[error("Invalid file extension.")]
+    InvalidExtension,
+    #[error("Could not write {0}.")]
+    WriteError(String),
+}
+
+/// The default title of the verschil document.
+pub const BIJLAGE_TITEL: &str = "Bijlage 1 : regularisatie loonverschil achterstallen";
+
+/// The name of the sheet holding the difference table.
+pub const SHEET_NAME: &str = "Verschil";
+
+/// The row holding the column headers, the difference lines start on the next row.
+pub const HEADER_ROW: u32 = 4;
+
+const HEADERS: [&str; 8] = ["Kwart", "WGC", "WNK", "INSZ", "LC", "Loonbestand", "DMFA", "Verschil"];
+
+/// The verschil document for a list of differences, as returned by `compare_dmfa_cipal` or `compare_dmfa_bosa`.
+///
+/// The sheet starts with the title, followed by the header on row 4 and one line per difference. The
+/// Verschil column is a formula (Loonbestand - DMFA), every INSZ with more than one line gets a "Subtotaal"
+/// row, and the last row is the "Totaal". Below the table the looncodes and werknemerskengetallen used in
+/// the table are explained from the catalogue of codes, a code missing from it is marked as unknown. The INSZ
+/// numbers are written according to the privacy profile.
+#[derive(Debug)]
+pub struct VerschilReport<'a> {
+    pub titel: String,
+    pub subtotalen: bool,
+    pub privacy: Privacy,
+    pub codes: Codes,
+    verschillen: &'a [Verschil],
+}
+
+impl<'a> VerschilReport<'a> {
+    /// Creates a new `VerschilReport` with the default title, subtotals per INSZ, masked INSZ numbers and the
+    /// standard catalogue of codes.
+    ///
+    /// # Arguments
+    ///
+    /// * `verschillen` - The difference lines, in the order they have to appear in the table.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::path::Path;
+    /// use lvgl::{compare_dmfa_cipal, Mapping, VerschilReport};
+    ///
+    /// let vergelijking = compare_dmfa_cipal(Path::new("dmfa.xlsx"), Path::new("cipal.xlsx"), &Mapping::standaard()).unwrap();
+    /// VerschilReport::new(&vergelijking.verschillen).write("verschil.xlsx").unwrap();
+    /// ```
+    pub fn new(verschillen: &'a [Verschil]) -> Self {
+        VerschilReport {
+            titel: BIJLAGE_TITEL.to_string(),
+            subtotalen: true,
+            privacy: Privacy::default(),
+            codes: Codes::standaard(),
+            verschillen,
+        }
+    }
+
+    /// Builds the workbook in memory.
+    pub fn to_spreadsheet(&self) -> Spreadsheet {
+        let mut book = umya_spreadsheet::new_file();
+        let sheet = book.get_sheet_mut(&0).unwrap();
+        sheet.set_name(SHEET_NAME);
+
+        sheet.get_cell_mut((1, 1)).set_value(self.titel.as_str());
+        sheet.get_style_mut((1, 1)).get_font_mut().set_bold(true);
+
+        for (column, header) in HEADERS.iter().enumerate() {
+            let column = column as u32 + 1;
+            sheet.get_cell_mut((column, HEADER_ROW)).set_value(*header);
+            sheet.get_style_mut((column, HEADER_ROW)).get_font_mut().set_bold(true);
+        }
+
+        let first = HEADER_ROW + 1;
+        let mut row = first;
+
+        for groep in self.verschillen.chunk_by(|a, b| a.key.insz == b.key.insz) {
+            let groep_start = row;
+            for verschil in groep {
+                let key = &verschil.key;
+                sheet.get_cell_mut((1, row)).set_value_number(key.kwart.year as u32 * 10 + key.kwart.quarter as u32);
+                sheet.get_cell_mut((2, row)).set_value_number(key.wgc);
+                sheet.get_cell_mut((3, row)).set_value_number(key.wnk);
+                // A text cell, so the leading zero of an INSZ is kept
+                sheet.get_cell_mut((4, row)).set_value_string(self.privacy.insz(&key.insz));
+                sheet.get_cell_mut((5, row)).set_value_number(key.lc);
+                set_bedrag(sheet, 6, row, verschil.loonbestand, None);
+                set_bedrag(sheet, 7, row, verschil.dmfa, None);
+                set_bedrag(sheet, 8, row, verschil.verschil(), Some(format!("F{row}-G{row}")));
+                row += 1;
+            }
+
+            if self.subtotalen && groep.len() > 1 {
+                let subtotaal: Money = groep.iter().map(Verschil::verschil).sum();
+                sheet.get_cell_mut((7, row)).set_value("Subtotaal");
+                sheet.get_style_mut((7, row)).get_font_mut().set_bold(true);
+                set_bedrag(sheet, 8, row, subtotaal, Some(format!("SUBTOTAL(9,H{groep_start}:H{})", row - 1)));
+                sheet.get_style_mut((8, row)).get_font_mut().set_bold(true);
+                row += 1;
+            }
+        }
+
+        // SUBTOTAL skips the cells holding a SUBTOTAL themselves, so the subtotals are not counted twice.
+        let totaal: Money = self.verschillen.iter().map(Verschil::verschil).sum();
+        sheet.get_cell_mut((7, row)).set_value("Totaal");
+        sheet.get_style_mut((7, row)).get_font_mut().set_bold(true);
+        let formule = if row > first { format!("SUBTOTAL(9,H{first}:H{})", row - 1) } else { "0".to_string() };
+        set_bedrag(sheet, 8, row, totaal, Some(formule));
+        sheet.get_style_mut((8, row)).get_font_mut().set_bold(true);
+
+        self.write_omschrijvingen(sheet, row + 3);
+
+        for (column, width) in ["A", "B", "C", "D", "E", "F", "G", "H"].iter().zip([8.0, 6.0, 6.0, 13.0, 5.0, 13.0, 13.0, 13.0]) {
+            sheet.get_column_dimension_mut(column).set_width(width);
+        }
+
+        book
+    }
+
+    /// Writes the workbook to an XLSX file.
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - A string slice that holds the filename of the verschil document.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ReportError` if the filename is empty, does not end in .xlsx or the file can not be written.
+    pub fn write(&self, filename: &str) -> Result<(), ReportError> {
+        if filename.is_empty() {
+            return Err(ReportError::InvalidFilename);
+        }
+
+        if !filename.to_lowercase().ends_with(".xlsx") {
+            return Err(ReportError::InvalidExtension);
+        }
+
+        umya_spreadsheet::writer::xlsx::write(&self.to_spreadsheet(), Path::new(filename))
+            .map_err(|_| ReportError::WriteError(filename.to_string()))
+    }
+
+    /// Explains the looncodes (code in column A) and werknemerskengetallen (code in column B) used in the
+    /// table, with their description in the catalogue for the quarter of the lines or as unknown. A code whose
+    /// description changed between the quarters of the table is explained once per description.
+    fn write_omschrijvingen(&self, sheet: &mut Worksheet, mut row: u32) {
+        // Sorted by code and quarter, so the descriptions of a code follow its quarters
+        let omschrijvingen = |soort: Codesoort, onbekend: &'static str, code: fn(&LoonKey) -> u16| {
+            let mut omschrijvingen: Vec<(u16, &str)> = self.verschillen.iter()
+                .map(|verschil| (code(&verschil.key), &verschil.key.kwart))
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .map(|(code, kwart)| (code, self.codes.omschrijving(soort, code, kwart).unwrap_or(onbekend)))
+                .collect();
+            omschrijvingen.dedup();
+            omschrijvingen
+        };
+
+        for (lc, omschrijving) in omschrijvingen(Codesoort::Lc, "Onbekende looncode", |key| key.lc) {
+            sheet.get_cell_mut((1, row)).set_value_number(lc);
+            sheet.get_cell_mut((2, row)).set_value(omschrijving);
+            row += 1;
+        }
+
+        row += 2;
+
+        for (wnk, omschrijving) in omschrijvingen(Codesoort::Wnk, "Onbekend werknemerskengetal", |key| key.wnk) {
+            sheet.get_cell_mut((2, row)).set_value_number(wnk);
+            sheet.get_cell_mut((3, row)).set_value(omschrijving);
+            row += 1;
+        }
+    }
+}
+
+/// Writes an amount with its cached value, and optionally the formula that calculates it.
+fn set_bedrag(sheet: &mut Worksheet, column: u32, row: u32, bedrag: Money, formule: Option<String>) {
+    let cell = sheet.get_cell_mut((column, row));
+    cell.set_value_number(bedrag.to_f64());
+    if let Some(formule) = formule {
+        cell.set_formula(formule);
+    }
+    sheet.get_style_mut((column, row))
+        .get_number_format_mut()
+        .set_format_code(NumberingFormat::FORMAT_NUMBER_COMMA_SEPARATED1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use umya_spreadsheet::reader::xlsx;
+    use tempfile::Builder;
+    use crate::compare::LoonKey;
+    use crate::privacy::PrivacyProfile;
+    use crate::types::{Kwartaal, Rrn};
+
+    fn verschil(kwart: &str, insz: &str, wnk: u16, lc: u16, loonbestand: f64, dmfa: f64) -> Verschil {
+        Verschil {
+            key: LoonKey {
+                kwart: Kwartaal::new(kwart.to_string()).unwrap(),
+                wgc: 751,
+                wnk,
+                insz: Rrn::new(insz).unwrap(),
+                lc,
+            },
+            loonbestand: Money::from_f64(loonbestand).unwrap(),
+            dmfa: Money::from_f64(dmfa).unwrap(),
+        }
+    }
+
+    fn verschillen() -> Vec<Verschil> {
+        vec![
+            verschil("20231", "01032412205", 495, 1, 5676.99, 4786.42),
+            verschil("20232", "01032412205", 495, 1, 6386.61, 5440.95),
+            verschil("20233", "96071725638", 405, 46, 281.42, 0.0),
+        ]
+    }
+
+    #[test]
+    fn test_verschil_report_layout() {
+        let verschillen = verschillen();
+        let mut report = VerschilReport::new(&verschillen);
+        report.privacy = Privacy::new(PrivacyProfile::Clear, b"").unwrap();
+        let book = report.to_spreadsheet();
+        let sheet = book.get_sheet_by_name(SHEET_NAME).unwrap();
+
+        assert_eq!(sheet.get_value((1, 1)), BIJLAGE_TITEL);
+        assert_eq!(sheet.get_value((1, 4)), "Kwart");
+        assert_eq!(sheet.get_value((8, 4)), "Verschil");
+
+        assert_eq!(sheet.get_value((1, 5)), "20231");
+        assert_eq!(sheet.get_value((4, 5)), "01032412205");
+        assert_eq!(sheet.get_cell((8, 5)).unwrap().get_formula(), "F5-G5");
+        assert_eq!(sheet.get_value((8, 5)), "890.57");
+
+        // Two lines for the first INSZ, so a subtotal ; none for the single line of the second INSZ.
+        assert_eq!(sheet.get_value((7, 7)), "Subtotaal");
+        assert_eq!(sheet.get_cell((8, 7)).unwrap().get_formula(), "SUBTOTAL(9,H5:H6)");
+        assert_eq!(sheet.get_value((1, 8)), "20233");
+        assert_eq!(sheet.get_value((7, 9)), "Totaal");
+        assert_eq!(sheet.get_cell((8, 9)).unwrap().get_formula(), "SUBTOTAL(9,H5:H8)");
+        assert_eq!(sheet.get_value((8, 9)), "2117.65");
+
+        // The annex explains the looncodes 1 and 46 and the kengetallen 405 and 495 from the catalogue.
+        assert_eq!(sheet.get_value((1, 12)), "1");
+        assert_eq!(sheet.get_value((2, 12)), "Bezoldiging");
+        assert_eq!(sheet.get_value((1, 13)), "46");
+        assert!(sheet.get_value((2, 13)).contains("Sociale Maribel"));
+        assert_eq!(sheet.get_value((2, 16)), "405");
+        assert_eq!(sheet.get_value((3, 16)), "Beschermde lokale mandatarissen");
+        assert_eq!(sheet.get_value((2, 17)), "495");
+    }
+
+    #[test]
+    fn test_verschil_report_onbekende_codes() {
+        let verschillen = verschillen();
+        let mut report = VerschilReport::new(&verschillen);
+        report.codes.looncodes.retain(|code| code.code != 1);
+        report.codes.werknemerskengetallen.clear();
+        let book = report.to_spreadsheet();
+        let sheet = book.get_sheet_by_name(SHEET_NAME).unwrap();
+
+        assert_eq!(sheet.get_value((2, 12)), "Onbekende looncode");
+        assert_eq!(sheet.get_value((3, 16)), "Onbekend werknemerskengetal");
+        assert_eq!(sheet.get_value((3, 17)), "Onbekend werknemerskengetal");
+    }
+
+    #[test]
+    fn test_verschil_report_omschrijving_per_kwartaal() {
+        let verschillen = verschillen();
+        let mut report = VerschilReport::new(&verschillen);
+        report.codes = Codes::from_toml(r#"
+            [[wnk]]
+            code = 495
+            nl = "Oud"
+            fr = "Ancien"
+            tot = 20231
+
+            [[wnk]]
+            code = 495
+            nl = "Nieuw"
+            fr = "Nouveau"
+            van = 20232
+        "#).unwrap();
+        let book = report.to_spreadsheet();
+        let sheet = book.get_sheet_by_name(SHEET_NAME).unwrap();
+
+        // The kengetal 495 of the lines of 20231 and 20232 has a description for each quarter
+        assert_eq!(sheet.get_value((2, 16)), "405");
+        assert_eq!(sheet.get_value((3, 16)), "Onbekend werknemerskengetal");
+        assert_eq!(sheet.get_value((2, 17)), "495");
+        assert_eq!(sheet.get_value((3, 17)), "Oud");
+        assert_eq!(sheet.get_value((2, 18)), "495");
+        assert_eq!(sheet.get_value((3, 18)), "Nieuw");
+    }
+
+    #[test]
+    fn test_verschil_report_without_subtotalen() {
+        let verschillen = verschillen();
+        let mut report = VerschilReport::new(&verschillen);
+        report.subtotalen = false;
+        report.titel = "Bijlage 2: loonverschil".to_string();
+        let book = report.to_spreadsheet();
+        let sheet = book.get_sheet_by_name(SHEET_NAME).unwrap();
+
+        assert_eq!(sheet.get_value((1, 1)), "Bijlage 2: loonverschil");
+        assert_eq!(sheet.get_value((1, 7)), "20233");
+        assert_eq!(sheet.get_value((7, 8)), "Totaal");
+        assert_eq!(sheet.get_cell((8, 8)).unwrap().get_formula(), "SUBTOTAL(9,H5:H7)");
+    }
+
+    #[test]
+    fn test_verschil_report_masked() {
+        let verschillen = verschillen();
+        let mut report = VerschilReport::new(&verschillen);
+        report.privacy = Privacy::new(PrivacyProfile::Masked, b"").unwrap();
+        let book = report.to_spreadsheet();
+        let sheet = book.get_sheet_by_name(SHEET_NAME).unwrap();
+
+        assert_eq!(sheet.get_value((4, 5)), "01.03.24-***.**");
+        assert_eq!(sheet.get_value((4, 8)), "96.07.17-***.**");
+        assert!(!(1..=sheet.get_highest_row()).any(|row| sheet.get_value((4, row)).contains("1032412205")));
+    }
+
+    #[test]
+    fn test_verschil_report_write() {
+        let verschillen = verschillen();
+        let file = Builder::new().suffix(".xlsx").tempfile().unwrap();
+        let mut report = VerschilReport::new(&verschillen);
+        report.privacy = Privacy::new(PrivacyProfile::Clear, b"").unwrap();
+        report.write(file.path().to_str().unwrap()).unwrap();
+
+        let book = xlsx::read(file.path()).unwrap();
+        let sheet = book.get_sheet_by_name(SHEET_NAME).unwrap();
+        assert_eq!(sheet.get_value((4, 5)), "01032412205");
+        assert_eq!(sheet.get_value((4, 8)), "96071725638");
+        assert_eq!(sheet.get_value((8, 8)), "281.42");
+        assert_eq!(sheet.get_value((8, 9)), "2117.65");
+    }
+
+    #[test]
+    fn test_verschil_report_write_invalid_extension() {
+        let verschillen = verschillen();
+        let result = VerschilReport::new(&verschillen).write("verschil.csv");
+        assert_eq!(result.unwrap_err(), ReportError::InvalidExtension);
+    }
+
+    #[test]
+    fn test_verschil_report_empty() {
+        let book = VerschilReport::new(&[]).to_spreadsheet();
+        let sheet = book.get_sheet_by_name(SHEET_NAME).unwrap();
+        assert_eq!(sheet.get_value((7, 5)), "Totaal");
+        assert_eq!(sheet.get_value((8, 5)), "0");
+    }
+}
@@ -0,0 +1,459 @@
+// Schema : the layout of every supported format (sheet, header rows, columns with their aliases and types),
+// used by the readers to find their columns and by `validate` to report every problem of a document at once.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+use thiserror::Error;
+use crate::bron::Bron;
+use crate::kolommen::{normaliseer, DmfaKolom, Kolommen};
+use crate::types::{Kbo, Kwartaal, Maand, Money, Rrn};
+use crate::xlsx::{Row, XlsxReader};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SchemaError {
+    #[error("File not found.")]
+    FileNotFound,
+    #[error("Sheet {0} not found.")]
+    SheetNotFound(String),
+    #[error("Invalid xlsx file: {0}")]
+    InvalidXlsx(String),
+    #[error("Columns not found: {}.", .0.join(", "))]
+    ColumnsNotFound(Vec<String>),
+}
+
+/// The type of the values of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kolomtype {
+    Tekst,
+    /// A whole number, CIPAL exports them as floats (e.g. `751.0`).
+    Geheel,
+    Bedrag,
+    Insz,
+    Kbo,
+    Kwartaal,
+    /// A month, `YYYYMM` (BOSA) or `MM/YYYY` (CIPAL).
+    Maand,
+}
+
+impl Kolomtype {
+    /// Whether a non-empty value is of this type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lvgl::Kolomtype;
+    ///
+    /// assert!(Kolomtype::Geheel.past("751.0"));
+    /// assert!(Kolomtype::Bedrag.past("1.622,95"));
+    /// assert!(!Kolomtype::Insz.past("NISS"));
+    /// assert!(Kolomtype::Insz.past("69.10.01-363.59"));
+    /// // A wrong control number
+    /// assert!(!Kolomtype::Insz.past("69.10.01-363.58"));
+    /// ```
+    pub fn past(&self, waarde: &str) -> bool {
+        let waarde = waarde.trim();
+        match self {
+            Kolomtype::Tekst => true,
+            Kolomtype::Geheel => waarde.strip_suffix(".0").unwrap_or(waarde).parse::<u64>().is_ok(),
+            Kolomtype::Bedrag => waarde.parse::<Money>().is_ok(),
+            Kolomtype::Insz => Rrn::new_checked(waarde).is_ok(),
+            Kolomtype::Kbo => Kbo::new(waarde).is_ok(),
+            Kolomtype::Kwartaal => Kwartaal::new(waarde.to_string()).is_ok(),
+            Kolomtype::Maand => Maand::new(waarde.to_string()).is_ok(),
+        }
+    }
+}
+
+impl fmt::Display for Kolomtype {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Kolomtype::Tekst => "tekst",
+            Kolomtype::Geheel => "geheel getal",
+            Kolomtype::Bedrag => "bedrag",
+            Kolomtype::Insz => "INSZ",
+            Kolomtype::Kbo => "KBO",
+            Kolomtype::Kwartaal => "kwartaal",
+            Kolomtype::Maand => "maand",
+        })
+    }
+}
+
+/// Whether a document must have a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verplicht {
+    Ja,
+    Nee,
+    /// Required, unless the document has all these other columns instead.
+    Tenzij(&'static [&'static str]),
+}
+
+/// One column of a format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KolomSchema {
+    /// The canonical name.
+    pub naam: &'static str,
+    /// The accepted headers, lowercase and without accents.
+    pub aliassen: &'static [&'static str],
+    pub soort: Kolomtype,
+    pub verplicht: Verplicht,
+}
+
+/// The layout of a format.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Schema {
+    pub bron: Bron,
+    pub naam: &'static str,
+    /// The sheet with the lines, the first sheet if `None`.
+    pub blad: Option<&'static str>,
+    /// The number of header rows.
+    pub kopregels: usize,
+    /// The number of title rows that may come before the header.
+    pub titelregels: usize,
+    /// The column that marks a line, rows where it is empty (subtotals, ...) are not checked.
+    pub sleutel: &'static str,
+    /// Whether the lines end at the first empty row, what follows (a legend, ...) is not checked.
+    pub einde_bij_lege_rij: bool,
+    pub kolommen: &'static [KolomSchema],
+}
+
+const fn kolom(naam: &'static str, aliassen: &'static [&'static str], soort: Kolomtype, verplicht: Verplicht) -> KolomSchema {
+    KolomSchema { naam, aliassen, soort, verplicht }
+}
+
+const fn dmfa(kolom: DmfaKolom, soort: Kolomtype) -> KolomSchema {
+    KolomSchema { naam: kolom.naam(), aliassen: kolom.aliassen(), soort, verplicht: Verplicht::Ja }
+}
+
+/// The DMFA export, with a Dutch and a French header row.
+pub static DMFA: Schema = Schema {
+    bron: Bron::Dmfa,
+    naam: "DMFA",
+    blad: None,
+    kopregels: 2,
+    titelregels: 0,
+    sleutel: "Kwart",
+    einde_bij_lege_rij: false,
+    kolommen: &[
+        dmfa(DmfaKolom::Kwart, Kolomtype::Kwartaal),
+        dmfa(DmfaKolom::RszNr, Kolomtype::Tekst),
+        dmfa(DmfaKolom::Kbo, Kolomtype::Kbo),
+        dmfa(DmfaKolom::Wgc, Kolomtype::Geheel),
+        dmfa(DmfaKolom::Wnk, Kolomtype::Geheel),
+        dmfa(DmfaKolom::Insz, Kolomtype::Insz),
+        dmfa(DmfaKolom::TNr, Kolomtype::Geheel),
+        dmfa(DmfaKolom::TBegin, Kolomtype::Geheel),
+        dmfa(DmfaKolom::TEind, Kolomtype::Geheel),
+        dmfa(DmfaKolom::Lc, Kolomtype::Geheel),
+        dmfa(DmfaKolom::LcBedr, Kolomtype::Bedrag),
+    ],
+};
+
+/// The "Data" sheet of the CIPAL report RP10088, the month is in "maand/jaar" or in "jaar" and "maand".
+pub static CIPAL: Schema = Schema {
+    bron: Bron::Cipal,
+    naam: "CIPAL RP10088",
+    blad: Some("Data"),
+    kopregels: 1,
+    titelregels: 0,
+    sleutel: "insznr",
+    einde_bij_lege_rij: false,
+    kolommen: &[
+        kolom("naam", &["naam"], Kolomtype::Tekst, Verplicht::Ja),
+        kolom("voornaam", &["voornaam"], Kolomtype::Tekst, Verplicht::Ja),
+        kolom("insznr", &["insznr"], Kolomtype::Insz, Verplicht::Ja),
+        kolom("persnr", &["persnr"], Kolomtype::Geheel, Verplicht::Ja),
+        kolom("maand/jaar", &["maand/jaar"], Kolomtype::Maand, Verplicht::Tenzij(&["jaar", "maand"])),
+        kolom("jaar", &["jaar"], Kolomtype::Geheel, Verplicht::Nee),
+        kolom("maand", &["maand"], Kolomtype::Geheel, Verplicht::Nee),
+        kolom("wgcategorie", &["wgcategorie"], Kolomtype::Geheel, Verplicht::Ja),
+        kolom("wnkengetal", &["wnkengetal"], Kolomtype::Geheel, Verplicht::Ja),
+        kolom("aard", &["aard"], Kolomtype::Geheel, Verplicht::Ja),
+        kolom("code", &["code"], Kolomtype::Geheel, Verplicht::Ja),
+        kolom("extensie", &["extensie"], Kolomtype::Geheel, Verplicht::Ja),
+        kolom("bezoldigingscode", &["bezoldigingscode"], Kolomtype::Geheel, Verplicht::Ja),
+        kolom("korte omschrijving looncode", &["korte omschrijving looncode"], Kolomtype::Tekst, Verplicht::Ja),
+        kolom("kostenplaats", &["kostenplaats"], Kolomtype::Geheel, Verplicht::Ja),
+        kolom("brutobedrag", &["brutobedrag"], Kolomtype::Bedrag, Verplicht::Ja),
+        kolom("patr.rsz", &["patr.rsz"], Kolomtype::Bedrag, Verplicht::Ja),
+        kolom("patr.pens", &["patr.pens"], Kolomtype::Bedrag, Verplicht::Ja),
+    ],
+};
+
+/// The BOSA export, one line per person, month and looncode.
+pub static BOSA: Schema = Schema {
+    bron: Bron::Bosa,
+    naam: "BOSA",
+    blad: None,
+    kopregels: 1,
+    titelregels: 0,
+    sleutel: "INSZ",
+    einde_bij_lege_rij: false,
+    kolommen: &[
+        kolom("KBO", &["kbo"], Kolomtype::Kbo, Verplicht::Ja),
+        kolom("INSZ", &["insz"], Kolomtype::Insz, Verplicht::Ja),
+        kolom("Periode", &["periode"], Kolomtype::Maand, Verplicht::Ja),
+        kolom("WGC", &["wgc"], Kolomtype::Geheel, Verplicht::Ja),
+        kolom("WNK", &["wnk"], Kolomtype::Geheel, Verplicht::Ja),
+        kolom("Looncode", &["looncode"], Kolomtype::Geheel, Verplicht::Ja),
+        kolom("Bedrag", &["bedrag"], Kolomtype::Bedrag, Verplicht::Ja),
+    ],
+};
+
+/// The verschil document, as written by `VerschilReport` (under a title, above the legend of the codes) or by
+/// the accountants. The INSZ may be masked or tokenised.
+pub static VERSCHIL: Schema = Schema {
+    bron: Bron::Verschil,
+    naam: "verschil",
+    blad: None,
+    kopregels: 1,
+    titelregels: 3,
+    sleutel: "Kwart",
+    einde_bij_lege_rij: true,
+    kolommen: &[
+        kolom("Kwart", &["kwart", "kwartaal"], Kolomtype::Kwartaal, Verplicht::Ja),
+        kolom("WGC", &["wgc"], Kolomtype::Geheel, Verplicht::Ja),
+        kolom("WNK", &["wnk"], Kolomtype::Geheel, Verplicht::Ja),
+        kolom("INSZ", &["insz"], Kolomtype::Tekst, Verplicht::Ja),
+        kolom("LC", &["lc", "looncode"], Kolomtype::Geheel, Verplicht::Ja),
+        kolom("Loonbestand", &["loonbestand", "loonbestand bosa", "loonbestand cipal"], Kolomtype::Bedrag, Verplicht::Ja),
+        kolom("DMFA", &["dmfa", "bedrag dmfa"], Kolomtype::Bedrag, Verplicht::Ja),
+        kolom("Verschil", &["verschil", "verschil = nog aan te geven dmfa"], Kolomtype::Bedrag, Verplicht::Ja),
+    ],
+};
+
+/// All supported formats.
+pub static SCHEMAS: [&Schema; 4] = [&DMFA, &CIPAL, &BOSA, &VERSCHIL];
+
+/// The schema of a source.
+///
+/// # Examples
+///
+/// ```
+/// use lvgl::{schema, Bron};
+///
+/// assert_eq!(schema(Bron::Cipal).naam, "CIPAL RP10088");
+/// assert_eq!(schema(Bron::Dmfa).kopregels, 2);
+/// ```
+pub fn schema(bron: Bron) -> &'static Schema {
+    match bron {
+        Bron::Dmfa => &DMFA,
+        Bron::Cipal => &CIPAL,
+        Bron::Bosa => &BOSA,
+        Bron::Verschil => &VERSCHIL,
+    }
+}
+
+/// A problem of a document, found by `Schema::validate`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Probleem {
+    /// A required column is missing.
+    Ontbreekt(String),
+    /// Values of a column are not of its type : how many, and the first one.
+    Type { kolom: String, soort: Kolomtype, aantal: usize, rij: u32, waarde: String },
+}
+
+impl fmt::Display for Probleem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Probleem::Ontbreekt(kolom) => write!(f, "Column {} not found.", kolom),
+            Probleem::Type { kolom, soort, aantal, rij, waarde } => write!(
+                f, "Column {}: {} invalid {} value(s), the first on row {} ({:?}).", kolom, aantal, soort, rij, waarde
+            ),
+        }
+    }
+}
+
+impl Schema {
+    /// The column with the canonical name `naam`.
+    pub fn kolom(&self, naam: &str) -> Option<&KolomSchema> {
+        self.kolommen.iter().find(|kolom| kolom.naam == naam)
+    }
+
+    /// Resolves the columns the header rows have, by exact (case and accent insensitive) name. A column is
+    /// taken from the first row that names it.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The header rows, in order.
+    pub fn zoek<'a>(&self, header: impl IntoIterator<Item = &'a Row>) -> Kolommen {
+        let mut kolommen = HashMap::new();
+        for row in header {
+            for (column, value) in row.cells() {
+                let value = normaliseer(value);
+                if let Some(kolom) = self.kolommen.iter().find(|kolom| kolom.aliassen.contains(&value.as_str())) {
+                    kolommen.entry(kolom.naam).or_insert(column);
+                }
+            }
+        }
+        Kolommen { kolommen }
+    }
+
+    /// Resolves the columns of the header rows, like `zoek`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SchemaError::ColumnsNotFound` with all the missing required columns.
+    pub fn kolommen<'a>(&self, header: impl IntoIterator<Item = &'a Row>) -> Result<Kolommen, SchemaError> {
+        let kolommen = self.zoek(header);
+        match self.ontbrekend(&kolommen) {
+            ontbrekend if ontbrekend.is_empty() => Ok(kolommen),
+            ontbrekend => Err(SchemaError::ColumnsNotFound(ontbrekend)),
+        }
+    }
+
+    /// The required columns that are missing.
+    fn ontbrekend(&self, kolommen: &Kolommen) -> Vec<String> {
+        self.kolommen.iter()
+            .filter(|kolom| match kolom.verplicht {
+                Verplicht::Ja => true,
+                Verplicht::Nee => false,
+                Verplicht::Tenzij(andere) => !kolommen.has(andere),
+            })
+            .filter(|kolom| kolommen.get(kolom.naam).is_none())
+            .map(|kolom| kolom.naam.to_string())
+            .collect()
+    }
+
+    /// Checks a document against the schema : every missing column and every column with values that are not
+    /// of its type, at once.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SchemaError` if the document can not be read or has no sheet of this format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::Path;
+    /// use lvgl::DMFA;
+    ///
+    /// assert!(DMFA.validate(Path::new("tests/fixtures/207527540-dmfa.xlsx")).unwrap().is_empty());
+    /// ```
+    pub fn validate(&self, path: &Path) -> Result<Vec<Probleem>, SchemaError> {
+        let mut workbook = XlsxReader::open(path).map_err(|_| SchemaError::FileNotFound)?;
+        let mut rows = match self.blad {
+            Some(blad) => workbook.sheet_by_name(blad).map_err(|_| SchemaError::SheetNotFound(blad.to_string()))?,
+            None => workbook.sheet(0).map_err(|_| SchemaError::SheetNotFound("1".to_string()))?,
+        };
+        let invalid = |e: crate::xlsx::XlsxError| SchemaError::InvalidXlsx(e.to_string());
+
+        // The header follows the title rows, if any : it is the first row naming the key column
+        let mut begin: Vec<Row> = rows.by_ref()
+            .take(self.titelregels + self.kopregels)
+            .collect::<Result<_, _>>()
+            .map_err(invalid)?;
+        let start = (0..=self.titelregels.min(begin.len()))
+            .find(|start| self.zoek(begin.get(*start)).get(self.sleutel).is_some())
+            .unwrap_or(0);
+        let lijnen = begin.split_off((start + self.kopregels).min(begin.len()));
+        let kolommen = self.zoek(&begin[start..]);
+
+        let mut problemen: Vec<Probleem> = self.ontbrekend(&kolommen).into_iter().map(Probleem::Ontbreekt).collect();
+
+        let gevonden: Vec<(&KolomSchema, u32)> = self.kolommen.iter()
+            .filter_map(|kolom| kolommen.get(kolom.naam).map(|column| (kolom, column)))
+            .filter(|(kolom, _)| kolom.soort != Kolomtype::Tekst)
+            .collect();
+        let sleutel = kolommen.get(self.sleutel);
+        let mut fouten: BTreeMap<u32, Probleem> = BTreeMap::new();
+        let mut vorige = begin.last().map_or(0, |row| row.number);
+        for row in lijnen.into_iter().map(Ok).chain(rows) {
+            let row = row.map_err(invalid)?;
+            let leeg = row.number > vorige + 1 || row.cells().next().is_none();
+            if self.einde_bij_lege_rij && leeg {
+                break;
+            }
+            vorige = row.number;
+            if sleutel.is_some_and(|column| row.get(column).trim().is_empty()) {
+                continue;
+            }
+            for (kolom, column) in &gevonden {
+                let waarde = row.get(*column);
+                if waarde.trim().is_empty() || kolom.soort.past(waarde) {
+                    continue;
+                }
+                match fouten.get_mut(column) {
+                    Some(Probleem::Type { aantal, .. }) => *aantal += 1,
+                    _ => {
+                        fouten.insert(*column, Probleem::Type {
+                            kolom: kolom.naam.to_string(),
+                            soort: kolom.soort,
+                            aantal: 1,
+                            rij: row.number,
+                            waarde: waarde.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+
+        problemen.extend(fouten.into_values());
+        Ok(problemen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::xlsx_file;
+
+    #[test]
+    fn test_validate_fixtures() {
+        for (path, schema) in [
+            ("tests/fixtures/207527540-dmfa.xlsx", &DMFA),
+            ("tests/fixtures/208322049-dmfa.xlsx", &DMFA),
+            ("tests/fixtures/207527540-cipal.xlsx", &CIPAL),
+            ("tests/fixtures/207527540-verschil.xlsx", &VERSCHIL),
+            ("tests/fixtures/208322049-verschil.xlsx", &VERSCHIL),
+        ] {
+            assert_eq!(schema.validate(Path::new(path)), Ok(vec![]), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_validate_problemen() {
+        // One missing column, and two columns with invalid values
+        let rows = [
+            ["KBO", "INSZ", "Periode", "WGC", "WNK", "Bedrag"],
+            ["0207527540", "50062049792", "202301", "751", "15", "12,50"],
+            ["0207527540", "50062049792", "202313", "751", "15", "twaalf"],
+            ["0207527540", "50062049792", "2023", "751", "15", "12,50"],
+            ["", "", "", "", "", "Totaal"],
+        ];
+        let file = xlsx_file(&rows);
+
+        assert_eq!(BOSA.validate(file.path()).unwrap(), vec![
+            Probleem::Ontbreekt("Looncode".to_string()),
+            Probleem::Type { kolom: "Periode".to_string(), soort: Kolomtype::Maand, aantal: 2, rij: 3, waarde: "202313".to_string() },
+            Probleem::Type { kolom: "Bedrag".to_string(), soort: Kolomtype::Bedrag, aantal: 1, rij: 3, waarde: "twaalf".to_string() },
+        ]);
+        assert_eq!(CIPAL.validate(file.path()), Err(SchemaError::SheetNotFound("Data".to_string())));
+    }
+
+    #[test]
+    fn test_kolommen() {
+        let mut workbook = XlsxReader::open("tests/fixtures/207527540-cipal.xlsx").unwrap();
+        let header = workbook.sheet_by_name("Data").unwrap().next().unwrap().unwrap();
+        let kolommen = CIPAL.kolommen([&header]).unwrap();
+        assert!(kolommen.get("insznr").is_some());
+
+        assert_eq!(
+            BOSA.kolommen([&header]),
+            Err(SchemaError::ColumnsNotFound(
+                ["KBO", "INSZ", "Periode", "WGC", "WNK", "Looncode", "Bedrag"].map(String::from).to_vec()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_schema() {
+        for schema in SCHEMAS {
+            assert_eq!(super::schema(schema.bron), schema);
+            assert!(schema.kolom(schema.sleutel).is_some());
+            for kolom in schema.kolommen {
+                assert!(kolom.aliassen.iter().all(|alias| normaliseer(alias) == *alias), "{}", kolom.naam);
+            }
+        }
+    }
+}
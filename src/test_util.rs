@@ -0,0 +1,42 @@
+// Helpers of the unit tests : small workbooks written on the fly instead of extra fixtures.
+
+use tempfile::{Builder, NamedTempFile};
+
+/// The header row of a BOSA export.
+const BOSA_HEADER: [&str; 7] = ["KBO", "INSZ", "Periode", "WGC", "WNK", "Looncode", "Bedrag"];
+
+/// Writes the rows to the first sheet of a new xlsx workbook, from cell A1.
+pub(crate) fn xlsx_file<R: AsRef<[S]>, S: AsRef<str>>(rows: &[R]) -> NamedTempFile {
+    xlsx_sheet("Sheet1", rows)
+}
+
+/// Writes the rows to the first sheet of a new xlsx workbook with the name of the sheet, from cell A1.
+pub(crate) fn xlsx_sheet<R: AsRef<[S]>, S: AsRef<str>>(name: &str, rows: &[R]) -> NamedTempFile {
+    let file = Builder::new().suffix(".xlsx").tempfile().unwrap();
+    let mut book = umya_spreadsheet::new_file();
+    let sheet = book.get_sheet_mut(&0).unwrap();
+    sheet.set_name(name);
+    for (row, values) in rows.iter().enumerate() {
+        for (column, value) in values.as_ref().iter().enumerate() {
+            sheet.get_cell_mut((column as u32 + 1, row as u32 + 1)).set_value(value.as_ref());
+        }
+    }
+    umya_spreadsheet::writer::xlsx::write(&book, file.path()).unwrap();
+    file
+}
+
+/// Writes a BOSA export : the header row and the rows below it.
+pub(crate) fn bosa_file(rows: &[[&str; 7]]) -> NamedTempFile {
+    let mut rijen = vec![BOSA_HEADER];
+    rijen.extend_from_slice(rows);
+    xlsx_file(&rijen)
+}
+
+/// Writes a broken xlsx workbook : a zip archive with a workbook part, but without the relations of its sheets.
+pub(crate) fn broken_xlsx() -> NamedTempFile {
+    let file = Builder::new().suffix(".xlsx").tempfile().unwrap();
+    let mut writer = zip::ZipWriter::new(std::fs::File::create(file.path()).unwrap());
+    writer.start_file("xl/workbook.xml", zip::write::SimpleFileOptions::default()).unwrap();
+    writer.finish().unwrap();
+    file
+}
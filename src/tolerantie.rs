@@ -0,0 +1,327 @@
+// Tolerance and materiality of the differences.
+// Not every cent of difference matters : rounding noise is within tolerance, and what is left is minor or
+// material depending on how much it adds up to for one person in one quarter.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use serde::{Deserialize, Deserializer};
+use thiserror::Error;
+use crate::compare::Verschil;
+use crate::types::{Kwartaal, Money, Rrn};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum TolerantieError {
+    #[error("Invalid filename.")]
+    InvalidFilename,
+    #[error("File not found.")]
+    FileNotFound,
+    #[error("Invalid file extension.")]
+    InvalidExtension,
+    #[error("Invalid TOML tolerantie : {0}")]
+    Toml(String),
+    #[error("Invalid percentage {0}.")]
+    InvalidPercentage(f64),
+    #[error("Invalid klasse {0}, expected binnen, klein or materieel.")]
+    InvalidKlasse(String),
+}
+
+/// The class of a difference, from harmless to material.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Klasse {
+    /// Within the absolute or relative tolerance of the line, e.g. rounding noise.
+    BinnenTolerantie,
+    /// Outside the tolerance, but below the materiality of the person in the quarter.
+    Klein,
+    /// Outside the tolerance, and at or above the materiality of the person in the quarter.
+    Materieel,
+}
+
+impl fmt::Display for Klasse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Klasse::BinnenTolerantie => "binnen",
+            Klasse::Klein => "klein",
+            Klasse::Materieel => "materieel",
+        })
+    }
+}
+
+impl FromStr for Klasse {
+    type Err = TolerantieError;
+
+    fn from_str(klasse: &str) -> Result<Self, Self::Err> {
+        match klasse.trim().to_lowercase().as_str() {
+            "binnen" | "within" => Ok(Klasse::BinnenTolerantie),
+            "klein" | "minor" => Ok(Klasse::Klein),
+            "materieel" | "material" => Ok(Klasse::Materieel),
+            _ => Err(TolerantieError::InvalidKlasse(klasse.to_string())),
+        }
+    }
+}
+
+/// Overrides the tolerance of one looncode.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct LooncodeTolerantie {
+    pub lc: u16,
+    #[serde(default, deserialize_with = "optioneel_bedrag")]
+    pub absoluut: Option<Money>,
+    pub relatief: Option<f64>,
+}
+
+/// The tolerance settings, by default every difference of a cent or more is material.
+///
+/// A line is within tolerance if its difference is at most the absolute tolerance, or at most the relative
+/// tolerance (a percentage of the largest of the loonbestand and the DMFA amount). The other lines are summed
+/// (in absolute value) per person and quarter : if that sum reaches the materiality, they are material.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Tolerantie {
+    #[serde(default, deserialize_with = "bedrag")]
+    pub absoluut: Money,
+    #[serde(default)]
+    pub relatief: f64,
+    #[serde(default, deserialize_with = "bedrag")]
+    pub materialiteit: Money,
+    #[serde(default, rename = "looncode")]
+    pub looncodes: Vec<LooncodeTolerantie>,
+}
+
+impl Tolerantie {
+    /// Loads the tolerance settings from a TOML file.
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - A string slice that holds the filename of the settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TolerantieError` if the filename is invalid, the file is not found, the extension is not
+    /// .toml, or the content is not valid.
+    pub fn load(filename: &str) -> Result<Self, TolerantieError> {
+        if filename.is_empty() {
+            return Err(TolerantieError::InvalidFilename);
+        }
+
+        let extension = Path::new(filename).extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+        if extension.as_deref() != Some("toml") {
+            return Err(TolerantieError::InvalidExtension);
+        }
+
+        let content = fs::read_to_string(filename).map_err(|_| TolerantieError::FileNotFound)?;
+        Tolerantie::from_toml(&content)
+    }
+
+    /// Parses the tolerance settings, amounts can be given as numbers or in Dutch notation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lvgl::{Money, Tolerantie};
+    ///
+    /// let tolerantie = Tolerantie::from_toml(r#"
+    ///     absoluut = 0.05
+    ///     relatief = 0.1
+    ///     materialiteit = "100,00"
+    ///
+    ///     [[looncode]]
+    ///     lc = 2
+    ///     absoluut = 1
+    /// "#).unwrap();
+    /// assert_eq!(tolerantie.materialiteit, Money::from_cents(10000));
+    /// assert_eq!(tolerantie.looncodes[0].absoluut, Some(Money::from_cents(100)));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `TolerantieError::Toml` if the content can not be parsed, or `TolerantieError::InvalidPercentage`
+    /// if a relative tolerance is negative or above 100.
+    pub fn from_toml(content: &str) -> Result<Self, TolerantieError> {
+        let tolerantie: Tolerantie = toml::from_str(content).map_err(|e| TolerantieError::Toml(e.message().to_string()))?;
+        tolerantie.validate()?;
+        Ok(tolerantie)
+    }
+
+    fn validate(&self) -> Result<(), TolerantieError> {
+        let percentages = std::iter::once(self.relatief).chain(self.looncodes.iter().filter_map(|looncode| looncode.relatief));
+        for percentage in percentages {
+            if !(0.0..=100.0).contains(&percentage) {
+                return Err(TolerantieError::InvalidPercentage(percentage));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks if the difference of a single line is within tolerance.
+    pub fn binnen_tolerantie(&self, verschil: &Verschil) -> bool {
+        let looncode = self.looncodes.iter().find(|looncode| looncode.lc == verschil.key.lc);
+        let absoluut = looncode.and_then(|looncode| looncode.absoluut).unwrap_or(self.absoluut);
+        let relatief = looncode.and_then(|looncode| looncode.relatief).unwrap_or(self.relatief);
+
+        let basis = verschil.loonbestand.abs().max(verschil.dmfa.abs());
+        let relatief = Money::from_cents((basis.cents() as f64 * relatief / 100.0).round() as i64);
+
+        let bedrag = verschil.verschil().abs();
+        bedrag <= absoluut || bedrag <= relatief
+    }
+
+    /// Classifies the differences, in the same order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lvgl::{Klasse, Money, Tolerantie};
+    ///
+    /// let tolerantie = Tolerantie { absoluut: Money::from_cents(5), ..Default::default() };
+    /// assert!(tolerantie.klassen(&[]).is_empty());
+    /// assert!(Klasse::Materieel > Klasse::Klein);
+    /// ```
+    pub fn klassen(&self, verschillen: &[Verschil]) -> Vec<Klasse> {
+        let binnen: Vec<bool> = verschillen.iter().map(|verschil| self.binnen_tolerantie(verschil)).collect();
+
+        let mut per_persoon: HashMap<(&Rrn, &Kwartaal), Money> = HashMap::new();
+        for (verschil, _) in verschillen.iter().zip(&binnen).filter(|(_, binnen)| !**binnen) {
+            *per_persoon.entry((&verschil.key.insz, &verschil.key.kwart)).or_default() += verschil.verschil().abs();
+        }
+
+        verschillen.iter().zip(binnen)
+            .map(|(verschil, binnen)| {
+                if binnen {
+                    Klasse::BinnenTolerantie
+                } else if per_persoon[&(&verschil.key.insz, &verschil.key.kwart)] >= self.materialiteit {
+                    Klasse::Materieel
+                } else {
+                    Klasse::Klein
+                }
+            })
+            .collect()
+    }
+
+    /// The number of differences per class.
+    pub fn telling(&self, verschillen: &[Verschil]) -> BTreeMap<Klasse, usize> {
+        let mut telling = BTreeMap::new();
+        for klasse in self.klassen(verschillen) {
+            *telling.entry(klasse).or_default() += 1;
+        }
+        telling
+    }
+}
+
+/// Accepts an amount as a TOML number (`0.05`) or as a string in Dutch notation (`"0,05"`).
+fn bedrag<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Money, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Bedrag {
+        Integer(i64),
+        Float(f64),
+        Text(String),
+    }
+
+    match Bedrag::deserialize(deserializer)? {
+        Bedrag::Integer(euro) => Ok(Money::from_cents(euro * 100)),
+        Bedrag::Float(bedrag) => Money::from_f64(bedrag).map_err(serde::de::Error::custom),
+        Bedrag::Text(bedrag) => bedrag.parse().map_err(serde::de::Error::custom),
+    }
+}
+
+fn optioneel_bedrag<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Money>, D::Error> {
+    bedrag(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compare::LoonKey;
+
+    fn verschil(kwart: &str, insz: &str, lc: u16, loonbestand: &str, dmfa: &str) -> Verschil {
+        Verschil {
+            key: LoonKey {
+                kwart: Kwartaal::new(kwart.to_string()).unwrap(),
+                wgc: 751,
+                wnk: 495,
+                insz: Rrn::new(insz).unwrap(),
+                lc,
+            },
+            loonbestand: loonbestand.parse().unwrap(),
+            dmfa: dmfa.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_tolerantie_default() {
+        // Without settings every difference is material
+        let verschillen = [verschil("20231", "50062049792", 1, "100.01", "100.00")];
+        assert_eq!(Tolerantie::default().klassen(&verschillen), [Klasse::Materieel]);
+    }
+
+    #[test]
+    fn test_tolerantie_absoluut_en_relatief() {
+        let tolerantie = Tolerantie { absoluut: Money::from_cents(5), relatief: 0.1, ..Default::default() };
+        assert!(tolerantie.binnen_tolerantie(&verschil("20231", "50062049792", 1, "10.05", "10.00")));
+        assert!(!tolerantie.binnen_tolerantie(&verschil("20231", "50062049792", 1, "10.06", "10.00")));
+        // 0.1% of 5000.00 is 5.00
+        assert!(tolerantie.binnen_tolerantie(&verschil("20231", "50062049792", 1, "5000.00", "4995.00")));
+        assert!(!tolerantie.binnen_tolerantie(&verschil("20231", "50062049792", 1, "5000.00", "4994.99")));
+        assert!(tolerantie.binnen_tolerantie(&verschil("20231", "50062049792", 1, "-4995.00", "-5000.00")));
+    }
+
+    #[test]
+    fn test_tolerantie_looncode() {
+        let tolerantie = Tolerantie::from_toml(r#"
+            absoluut = 0.05
+
+            [[looncode]]
+            lc = 2
+            absoluut = "1,00"
+        "#).unwrap();
+        assert!(tolerantie.binnen_tolerantie(&verschil("20231", "50062049792", 2, "11.00", "10.00")));
+        assert!(!tolerantie.binnen_tolerantie(&verschil("20231", "50062049792", 1, "11.00", "10.00")));
+    }
+
+    #[test]
+    fn test_tolerantie_materialiteit() {
+        let tolerantie = Tolerantie {
+            absoluut: Money::from_cents(1),
+            materialiteit: Money::from_cents(5000),
+            ..Default::default()
+        };
+        let verschillen = [
+            verschil("20231", "50062049792", 1, "130.00", "100.00"),
+            verschil("20231", "50062049792", 2, "0.00", "25.00"),   // Opposite sign, still adds up to 55.00
+            verschil("20231", "50062049792", 3, "0.01", "0.00"),
+            verschil("20232", "50062049792", 1, "130.00", "100.00"),
+            verschil("20231", "65040644490", 1, "49.99", "0.00"),
+        ];
+        assert_eq!(tolerantie.klassen(&verschillen), [
+            Klasse::Materieel,
+            Klasse::Materieel,
+            Klasse::BinnenTolerantie,
+            Klasse::Klein,
+            Klasse::Klein,
+        ]);
+
+        let telling = tolerantie.telling(&verschillen);
+        assert_eq!(telling[&Klasse::Klein], 2);
+        assert_eq!(telling.get(&Klasse::BinnenTolerantie), Some(&1));
+    }
+
+    #[test]
+    fn test_tolerantie_invalid() {
+        assert_eq!(Tolerantie::from_toml("relatief = 120").unwrap_err(), TolerantieError::InvalidPercentage(120.0));
+        assert!(matches!(Tolerantie::from_toml("absoluut = \"veel\"").unwrap_err(), TolerantieError::Toml(_)));
+        assert_eq!(Tolerantie::load("tolerantie.txt").unwrap_err(), TolerantieError::InvalidExtension);
+        assert_eq!(Tolerantie::load("tests/fixtures/nonexistent.toml").unwrap_err(), TolerantieError::FileNotFound);
+    }
+
+    #[test]
+    fn test_klasse_from_str() {
+        assert_eq!("material".parse::<Klasse>().unwrap(), Klasse::Materieel);
+        assert_eq!("Klein".parse::<Klasse>().unwrap(), Klasse::Klein);
+        assert_eq!("binnen".parse::<Klasse>().unwrap(), Klasse::BinnenTolerantie);
+        assert_eq!("groot".parse::<Klasse>().unwrap_err(), TolerantieError::InvalidKlasse("groot".to_string()));
+        assert_eq!(format!("{:>9}", Klasse::Klein), "    klein");
+    }
+}
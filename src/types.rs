@@ -1,9 +1,13 @@
 // RijksRegisterNummer (RRN) is a Belgian national identification number.
+use std::collections::HashSet;
 use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
 use thiserror::Error;
+use time::{Date, Month};
 use std::num::ParseIntError;
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Gender {
     M,
     F,
@@ -18,17 +22,53 @@ impl fmt::Debug for Gender {
     }
 }
 
+/// The gender as in Dutch documents : M (man) or V (vrouw).
+impl fmt::Display for Gender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Gender::M => "M",
+            Gender::F => "V",
+        })
+    }
+}
+
 #[derive(Error, Debug, PartialEq)]
 pub enum RrnError {
     #[error("Invalid Rijksregister Nummer Length.")]
     InvalidLength,
+    #[error("Invalid Rijksregister Nummer, it may only hold digits.")]
+    InvalidCharacter,
     #[error("Invalid Rijksregister Nummer.")]
     InvalidControl,
+    #[error("Invalid birth date in Rijksregister Nummer.")]
+    InvalidDate,
+    #[error("Gender unknown for this BIS Nummer.")]
+    UnknownGender,
     #[error("ParseInt error: {0}")]
     ParseIntError(#[from] ParseIntError),
 }   
 
-#[derive(Debug, PartialEq, Eq)]
+/// The kind of identification number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RrnKind {
+    /// Rijksregisternummer of a person registered in the Rijksregister.
+    Rijksregister,
+    /// BIS number of a person not registered in the Rijksregister (e.g. a foreign worker). The month of
+    /// birth is raised by 40 if the gender was known when the number was issued, and by 20 if it was not.
+    Bis { gender_known: bool },
+}
+
+/// The information encoded in a Rijksregister or BIS number.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RrnInfo {
+    pub kind: RrnKind,
+    pub birth_year: u16,            // Century resolved from the control number
+    pub birth_date: Option<Date>,   // None if the birth date is (partially) unknown, month or day 00
+    pub gender: Option<Gender>,     // None for a BIS number issued without gender
+    pub sequence: u16,              // Volgnummer : odd for men, even for women
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Rrn {
     rrn: String,
 }
@@ -42,7 +82,9 @@ impl Rrn {
     ///
     /// # Errors
     ///
-    /// Returns `RrnError::InvalidLength` if the length of the RRN is not 9, 10, or 11 characters.
+    /// Returns `RrnError::InvalidCharacter` if the RRN holds anything but digits and the `.` and `-` separators,
+    /// and `RrnError::InvalidLength` if the length of the RRN is not 8, 9, 10, or 11 digits. Shorter numbers are
+    /// left padded with zeros, as Excel drops the leading zeros of numeric cells.
     ///
     /// # Examples
     ///
@@ -54,21 +96,47 @@ impl Rrn {
     pub fn new(rrn: &str) -> Result<Self, RrnError> {
         let mut rrn = rrn.trim().replace(&['.', '-'][..], "");
 
+        if !rrn.chars().all(|c| c.is_ascii_digit()) {
+            return Err(RrnError::InvalidCharacter);
+        }
+
         match rrn.len() {
             11 => {},
             10 => { rrn = format!("0{}", rrn)},
             9 => { rrn = format!("00{}", rrn)},
+            8 => { rrn = format!("000{}", rrn)},
             _ => return Err(RrnError::InvalidLength),
         }
 
         Ok(Rrn { rrn })
     }
 
+    /// Creates a new `Rrn` instance like `new`, and checks its control number and birth date with `info`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `new` and `info`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lvgl::{Rrn, RrnError};
+    ///
+    /// assert!(Rrn::new_checked("69.10.01-363.59").is_ok());
+    /// assert_eq!(Rrn::new_checked("69.10.01-363.58"), Err(RrnError::InvalidControl));
+    /// ```
+    pub fn new_checked(rrn: &str) -> Result<Self, RrnError> {
+        let rrn = Rrn::new(rrn)?;
+        rrn.info()?;
+        Ok(rrn)
+    }
+
     /// Checks the validity of the RRN and determines the gender.
     ///
     /// # Errors
     ///
-    /// Returns `RrnError::InvalidControl` if the control number is invalid.
+    /// Returns `RrnError::InvalidControl` if the control number is invalid, `RrnError::InvalidDate` if the
+    /// birth date is invalid, and `RrnError::UnknownGender` for a BIS number issued without gender.
     ///
     /// # Examples
     ///
@@ -80,35 +148,165 @@ impl Rrn {
     /// assert_eq!(gender, Gender::M);
     /// ```
     pub fn check(&self) -> Result<Gender, RrnError> {
-        let base = self.rrn.chars().take(9).collect::<String>().parse::<u32>()?;
-        let control = self.rrn.chars().skip(9).collect::<String>().parse::<u32>()?;
-        let check = 97 - (base % 97);
-
-        if check == control { // Check for pre 2000
-            println!("Pre 2000");
-            let id = self.rrn.chars().skip(6).take(3).collect::<String>().parse::<u32>()?;
-
-            if id % 2 == 0 {
-                return Ok(Gender::F);
-            } else {
-                return Ok(Gender::M);
-            }
-        } else { // Check for post 2000
-            println!("Post 2000");
-            let check2 = 97 - ((base + 2000000000) % 97);
-
-            if check2 == control {
-                let id = self.rrn.chars().skip(6).take(3).collect::<String>().parse::<u32>()?;
-
-                if id % 2 == 0 {
-                    return Ok(Gender::F);
-                } else {
-                    return Ok(Gender::M);
-                }
-            } else {
-                return Err(RrnError::InvalidControl);
-            }
+        self.info()?.gender.ok_or(RrnError::UnknownGender)
+    }
+
+    /// Decodes the birth date, gender, sequence number and kind (Rijksregister or BIS) of the RRN.
+    ///
+    /// The control number is 97 - (the first 9 digits modulo 97) for people born before 2000, and the same
+    /// calculation with a 2 in front of the first 9 digits for people born from 2000 on.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RrnError::InvalidControl` if the control number is invalid, or `RrnError::InvalidDate` if the
+    /// month or day of birth is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lvgl::{Gender, Rrn, RrnKind};
+    ///
+    /// let info = Rrn::new("85.43.15-123.58").unwrap().info().unwrap();
+    /// assert_eq!(info.kind, RrnKind::Bis { gender_known: true });
+    /// assert_eq!(info.birth_year, 1985);
+    /// assert_eq!(info.gender, Some(Gender::M));
+    /// ```
+    pub fn info(&self) -> Result<RrnInfo, RrnError> {
+        let base = self.rrn[..9].parse::<u32>()?;
+        let control = self.rrn[9..].parse::<u32>()?;
+
+        let century = if 97 - (base % 97) == control {
+            1900
+        } else if 97 - ((base + 2000000000) % 97) == control {
+            2000
+        } else {
+            return Err(RrnError::InvalidControl);
+        };
+
+        let birth_year = century + self.rrn[..2].parse::<u16>()?;
+        let month = self.rrn[2..4].parse::<u8>()?;
+        let day = self.rrn[4..6].parse::<u8>()?;
+        let sequence = self.rrn[6..9].parse::<u16>()?;
+
+        let (kind, month) = match month {
+            0..=12 => (RrnKind::Rijksregister, month),
+            20..=32 => (RrnKind::Bis { gender_known: false }, month - 20),
+            40..=52 => (RrnKind::Bis { gender_known: true }, month - 40),
+            _ => return Err(RrnError::InvalidDate),
+        };
+
+        let birth_date = if month == 0 || day == 0 {
+            None
+        } else {
+            let month = Month::try_from(month).map_err(|_| RrnError::InvalidDate)?;
+            Some(Date::from_calendar_date(birth_year as i32, month, day).map_err(|_| RrnError::InvalidDate)?)
+        };
+
+        let gender = match kind {
+            RrnKind::Bis { gender_known: false } => None,
+            _ if sequence % 2 == 0 => Some(Gender::F),
+            _ => Some(Gender::M),
+        };
+
+        Ok(RrnInfo { kind, birth_year, birth_date, gender, sequence })
+    }
+}
+
+impl fmt::Display for Rrn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.rrn)
+    }
+}
+
+// Ondernemingsnummer (KBO/BCE) of the Kruispuntbank van Ondernemingen, also the base of the BTW number.
+#[derive(Error, Debug, PartialEq)]
+pub enum KboError {
+    #[error("Invalid KBO Nummer Length.")]
+    InvalidLength,
+    #[error("Invalid KBO Nummer, it has to start with 0 or 1.")]
+    InvalidFirstDigit,
+    #[error("Invalid KBO Nummer.")]
+    InvalidControl,
+    #[error("ParseInt error: {0}")]
+    ParseIntError(#[from] ParseIntError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Kbo {
+    kbo: String,
+}
+
+impl Kbo {
+    /// Creates a new `Kbo` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `kbo` - A string slice that holds the KBO number, with or without dots, spaces and the BE prefix.
+    ///
+    /// # Errors
+    ///
+    /// Returns `KboError::InvalidLength` if the KBO number is not 9 or 10 digits long. A 9 digit number is
+    /// left padded with a zero, a 10 digit number has to start with 0 or 1 (`KboError::InvalidFirstDigit`).
+    /// Returns `KboError::InvalidControl` if the last two digits are not 97 - (the first 8 digits modulo 97).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lvgl::Kbo;
+    ///
+    /// let kbo = Kbo::new("207.527.540").unwrap();
+    /// assert_eq!(kbo, Kbo::new("BE0207527540").unwrap());
+    /// ```
+    pub fn new(kbo: &str) -> Result<Self, KboError> {
+        let kbo = kbo.trim().replace(['.', ' ', '-'], "");
+        let kbo = kbo.strip_prefix("BE").or_else(|| kbo.strip_prefix("be")).unwrap_or(&kbo);
+
+        if !kbo.chars().all(|c| c.is_ascii_digit()) {
+            return Err(KboError::InvalidLength);
+        }
+
+        let kbo = match kbo.len() {
+            10 => kbo.to_string(),
+            9 => format!("0{}", kbo),
+            _ => return Err(KboError::InvalidLength),
+        };
+
+        if !kbo.starts_with(['0', '1']) {
+            return Err(KboError::InvalidFirstDigit);
         }
+
+        let base = kbo[..8].parse::<u32>()?;
+        let control = kbo[8..].parse::<u32>()?;
+        if 97 - (base % 97) != control {
+            return Err(KboError::InvalidControl);
+        }
+
+        Ok(Kbo { kbo })
+    }
+
+    /// The 10 digits of the KBO number, e.g. `0207527540`.
+    pub fn as_str(&self) -> &str {
+        &self.kbo
+    }
+
+    /// The BTW (VAT) number, e.g. `BE0207527540`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lvgl::Kbo;
+    ///
+    /// let kbo = Kbo::new("207527540").unwrap();
+    /// assert_eq!(kbo.to_btw(), "BE0207527540");
+    /// ```
+    pub fn to_btw(&self) -> String {
+        format!("BE{}", self.kbo)
+    }
+}
+
+impl fmt::Display for Kbo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", &self.kbo[..4], &self.kbo[4..7], &self.kbo[7..])
     }
 }
 
@@ -120,11 +318,13 @@ pub enum KwartaalError {
     InvalidQuarter,
     #[error("Invalid Length.")]
     InvalidLength,
+    #[error("Invalid Range, the start comes after the stop.")]
+    InvalidRange,
     #[error("ParseInt error: {0}")]
     ParseIntError(#[from] ParseIntError),
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Kwartaal {
     pub year: u16,
     pub quarter: u8,
@@ -160,6 +360,49 @@ impl Kwartaal {
 
         Ok(Self { year, quarter })
     }
+
+    /// The quarter a date belongs to.
+    pub fn from_date(date: Date) -> Self {
+        Kwartaal { year: date.year() as u16, quarter: (u8::from(date.month()) - 1) / 3 + 1 }
+    }
+
+    /// The next quarter, e.g. 20234 → 20241.
+    pub fn next(&self) -> Self {
+        match self.quarter {
+            4 => Kwartaal { year: self.year + 1, quarter: 1 },
+            quarter => Kwartaal { year: self.year, quarter: quarter + 1 },
+        }
+    }
+
+    /// The previous quarter, e.g. 20241 → 20234.
+    pub fn previous(&self) -> Self {
+        match self.quarter {
+            1 => Kwartaal { year: self.year - 1, quarter: 4 },
+            quarter => Kwartaal { year: self.year, quarter: quarter - 1 },
+        }
+    }
+
+    /// The months of the quarter, e.g. 4..=6 for the second quarter.
+    pub fn months(&self) -> RangeInclusive<u8> {
+        let first = (self.quarter - 1) * 3 + 1;
+        first..=first + 2
+    }
+
+    /// Checks if the month of the year belongs to the quarter.
+    pub fn contains_month(&self, year: u16, month: u8) -> bool {
+        self.year == year && self.months().contains(&month)
+    }
+
+    /// The first calendar day of the quarter.
+    pub fn first_day(&self) -> Date {
+        let month = Month::try_from(*self.months().start()).expect("a quarter starts in a valid month");
+        Date::from_calendar_date(self.year as i32, month, 1).expect("the first day of a month is valid")
+    }
+
+    /// The last calendar day of the quarter.
+    pub fn last_day(&self) -> Date {
+        self.next().first_day().previous_day().expect("the first day of a quarter has a previous day")
+    }
 }
 
 impl fmt::Debug for Kwartaal {
@@ -168,6 +411,98 @@ impl fmt::Debug for Kwartaal {
     }
 }
 
+impl fmt::Display for Kwartaal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&format!("{}{}", self.year, self.quarter))
+    }
+}
+
+impl FromStr for Kwartaal {
+    type Err = KwartaalError;
+
+    fn from_str(kwart: &str) -> Result<Self, Self::Err> {
+        Kwartaal::new(kwart.to_string())
+    }
+}
+
+/// A span of quarters, both included, like the `20213_20242` in the sheet name of a DMFA file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KwartaalRange {
+    pub start: Kwartaal,
+    pub stop: Kwartaal,
+}
+
+impl KwartaalRange {
+    /// Creates a new `KwartaalRange` instance.
+    ///
+    /// # Errors
+    ///
+    /// Returns `KwartaalError::InvalidRange` if the start comes after the stop.
+    pub fn new(start: Kwartaal, stop: Kwartaal) -> Result<Self, KwartaalError> {
+        if start > stop {
+            return Err(KwartaalError::InvalidRange);
+        }
+        Ok(KwartaalRange { start, stop })
+    }
+
+    /// Checks if the quarter lies within the range.
+    pub fn contains(&self, kwartaal: &Kwartaal) -> bool {
+        (&self.start..=&self.stop).contains(&kwartaal)
+    }
+
+    /// The number of quarters in the range.
+    pub fn len(&self) -> usize {
+        let start = self.start.year as usize * 4 + self.start.quarter as usize;
+        let stop = self.stop.year as usize * 4 + self.stop.quarter as usize;
+        stop - start + 1
+    }
+
+    /// A range always holds at least one quarter.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Iterates over the quarters of the range, in order.
+    pub fn iter(&self) -> impl Iterator<Item = Kwartaal> + '_ {
+        std::iter::successors(Some(self.start.clone()), |kwartaal| Some(kwartaal.next()))
+            .take_while(|kwartaal| kwartaal <= &self.stop)
+    }
+
+    /// The quarters of the range that are not present, e.g. the gaps in the history of a person.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lvgl::{Kwartaal, KwartaalRange};
+    ///
+    /// let range: KwartaalRange = "20231_20242".parse().unwrap();
+    /// let present: Vec<Kwartaal> = vec!["20231".parse().unwrap(), "20234".parse().unwrap()];
+    /// let missing = range.missing(&present);
+    /// assert_eq!(missing.len(), 4);
+    /// assert_eq!(missing[0].to_string(), "20232");
+    /// ```
+    pub fn missing<'a>(&self, present: impl IntoIterator<Item = &'a Kwartaal>) -> Vec<Kwartaal> {
+        let present: HashSet<&Kwartaal> = present.into_iter().collect();
+        self.iter().filter(|kwartaal| !present.contains(kwartaal)).collect()
+    }
+}
+
+impl fmt::Display for KwartaalRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}_{}", self.start, self.stop)
+    }
+}
+
+impl FromStr for KwartaalRange {
+    type Err = KwartaalError;
+
+    /// Parses a span like `20213_20242`.
+    fn from_str(range: &str) -> Result<Self, Self::Err> {
+        let (start, stop) = range.trim().split_once('_').ok_or(KwartaalError::InvalidLength)?;
+        KwartaalRange::new(start.parse()?, stop.parse()?)
+    }
+}
+
 #[derive(Error, Debug, PartialEq)]
 pub enum MonthError {
     #[error("Invalid Year.")]
@@ -181,23 +516,64 @@ pub enum MonthError {
 
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct BosaMonth {
+/// A calendar month, the period of the BOSA and CIPAL wage lines.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Maand {
     pub year: u16,
     pub month: u8,
 }
 
-impl BosaMonth {
+/// The BOSA period, `YYYYMM`.
+pub type BosaMonth = Maand;
+
+/// The CIPAL "maand/jaar", `MM/YYYY` or `M/YYYY`.
+pub type CipalMonth = Maand;
+
+impl Maand {
+    /// Creates a new `Maand` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `month` - The month as `YYYYMM` (BOSA), or as `MM/YYYY` or `M/YYYY` (CIPAL).
+    ///
+    /// # Errors
+    ///
+    /// Returns `MonthError::InvalidLength` if the month is in none of these formats, and
+    /// `MonthError::InvalidYear` or `MonthError::InvalidMonth` if it is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lvgl::Maand;
+    ///
+    /// assert_eq!(Maand::new("202307".to_string()).unwrap(), Maand::new("7/2023".to_string()).unwrap());
+    /// ```
     pub fn new(month: String) -> Result<Self, MonthError> {
         let month = month.trim();
 
-        match month.len() {
-            6 => {},
-            _ => return Err(MonthError::InvalidLength),
-        }
+        let (year, month) = match month.split_once('/') {
+            Some((month, year)) if (1..=2).contains(&month.len()) && year.len() == 4 => (year, month),
+            Some(_) => return Err(MonthError::InvalidLength),
+            None if month.len() == 6 && month.is_char_boundary(4) => (&month[..4], &month[4..]),
+            None => return Err(MonthError::InvalidLength),
+        };
 
-        let year = month.chars().take(4).collect::<String>().parse::<u16>()?;
+        Maand::from_parts(year.parse::<u16>()?, month.parse::<u8>()?)
+    }
 
+    /// Creates a new `Maand` from the separate "jaar" and "maand" columns of CIPAL, which may be read as
+    /// floats like `2023.0` and `7.0`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MonthError` if the year or the month is not a number or out of range.
+    pub fn from_jaar_maand(jaar: &str, maand: &str) -> Result<Self, MonthError> {
+        let jaar = jaar.trim().trim_end_matches(".0");
+        let maand = maand.trim().trim_end_matches(".0");
+        Maand::from_parts(jaar.parse::<u16>()?, maand.parse::<u8>()?)
+    }
+
+    fn from_parts(year: u16, month: u8) -> Result<Self, MonthError> {
         if year < 1970 { // let's use the epoch as a reference
             return Err(MonthError::InvalidYear);
         }
@@ -205,8 +581,6 @@ impl BosaMonth {
             return Err(MonthError::InvalidYear);
         }
 
-        let month = month.chars().skip(4).collect::<String>().parse::<u8>()?;
-
         if month < 1 {
             return Err(MonthError::InvalidMonth);
         }
@@ -219,68 +593,352 @@ impl BosaMonth {
     }
 
     pub fn to_kwartaal(&self) -> Kwartaal {
-        let quarter = match self.month {
-            1 | 2 | 3 => 1,
-            4 | 5 | 6 => 2,
-            7 | 8 | 9 => 3,
-            10 | 11 | 12 => 4,
-            _ => 0, // This can never happen
-        };
+        Kwartaal { year: self.year, quarter: (self.month - 1) / 3 + 1 }
+    }
+
+    /// The first calendar day of the month.
+    pub fn first_day(&self) -> Date {
+        let month = Month::try_from(self.month).expect("a Maand holds a valid month");
+        Date::from_calendar_date(self.year as i32, month, 1).expect("the first day of a month is valid")
+    }
 
-        Kwartaal { year: self.year, quarter }
+    /// The last calendar day of the month.
+    pub fn last_day(&self) -> Date {
+        let month = Month::try_from(self.month).expect("a Maand holds a valid month");
+        Date::from_calendar_date(self.year as i32, month, month.length(self.year as i32))
+            .expect("the last day of a month is valid")
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct CipalMonth {
-    pub year: u16,
-    pub month: u8,
+impl fmt::Display for Maand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&format!("{}{:02}", self.year, self.month))
+    }
 }
 
-impl CipalMonth {
-    pub fn new(month: String) -> Result<Self, MonthError> {
-        let mut month = month.trim().replace(&['/'][..], "");
+impl FromStr for Maand {
+    type Err = MonthError;
 
-        match month.len() {
-            5 => {
-                month = format!("0{}", month);
-            },
-            6 => {},
-            _ => return Err(MonthError::InvalidLength),
+    fn from_str(month: &str) -> Result<Self, Self::Err> {
+        Maand::new(month.to_string())
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum PeriodError {
+    #[error("Invalid Period {0}.")]
+    InvalidPeriod(String),
+    #[error("Invalid Granularity, a {from:?} can not be split into a {to:?}.")]
+    InvalidGranularity { from: Granularity, to: Granularity },
+    #[error("Month error: {0}")]
+    MonthError(#[from] MonthError),
+    #[error("Kwartaal error: {0}")]
+    KwartaalError(#[from] KwartaalError),
+}
+
+/// The size of a `Period`, from fine to coarse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Granularity {
+    Maand,
+    Kwartaal,
+    Jaar,
+}
+
+/// A month, a quarter or a year.
+///
+/// Periods are ordered by their first and last day, so a year comes before its first quarter, which comes
+/// before its first month.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Period {
+    Maand(Maand),
+    Kwartaal(Kwartaal),
+    Jaar(u16),
+}
+
+impl Period {
+    pub fn granularity(&self) -> Granularity {
+        match self {
+            Period::Maand(_) => Granularity::Maand,
+            Period::Kwartaal(_) => Granularity::Kwartaal,
+            Period::Jaar(_) => Granularity::Jaar,
         }
+    }
 
-        let year = month.chars().skip(2).take(4).collect::<String>().parse::<u16>()?;
+    /// The period of the given granularity this period belongs to, e.g. the quarter of a month.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PeriodError::InvalidGranularity` if the granularity is finer than the one of the period.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lvgl::{Granularity, Period};
+    ///
+    /// let period: Period = "7/2023".parse().unwrap();
+    /// assert_eq!(period.to(Granularity::Kwartaal).unwrap().to_string(), "20233");
+    /// assert_eq!(period.to(Granularity::Jaar).unwrap(), Period::Jaar(2023));
+    /// ```
+    pub fn to(&self, granularity: Granularity) -> Result<Period, PeriodError> {
+        match (self, granularity) {
+            (period, granularity) if period.granularity() == granularity => Ok(period.clone()),
+            (Period::Maand(maand), Granularity::Kwartaal) => Ok(Period::Kwartaal(maand.to_kwartaal())),
+            (Period::Maand(maand), Granularity::Jaar) => Ok(Period::Jaar(maand.year)),
+            (Period::Kwartaal(kwartaal), Granularity::Jaar) => Ok(Period::Jaar(kwartaal.year)),
+            (period, granularity) => Err(PeriodError::InvalidGranularity { from: period.granularity(), to: granularity }),
+        }
+    }
 
-        if year < 1970 { // let's use the epoch as a reference
-            return Err(MonthError::InvalidYear);
+    /// The first calendar day of the period.
+    pub fn first_day(&self) -> Date {
+        match self {
+            Period::Maand(maand) => maand.first_day(),
+            Period::Kwartaal(kwartaal) => kwartaal.first_day(),
+            Period::Jaar(year) => Date::from_calendar_date(*year as i32, Month::January, 1).expect("1 January is valid"),
         }
-        if year > 2100 {
-            return Err(MonthError::InvalidYear);
+    }
+
+    /// The last calendar day of the period.
+    pub fn last_day(&self) -> Date {
+        match self {
+            Period::Maand(maand) => maand.last_day(),
+            Period::Kwartaal(kwartaal) => kwartaal.last_day(),
+            Period::Jaar(year) => Date::from_calendar_date(*year as i32, Month::December, 31).expect("31 December is valid"),
         }
+    }
 
-        let month = month.chars().take(2).collect::<String>().parse::<u8>()?;
+    /// Checks if the other period lies completely within this period.
+    pub fn contains(&self, other: &Period) -> bool {
+        self.first_day() <= other.first_day() && other.last_day() <= self.last_day()
+    }
+}
 
-        if month < 1 {
-            return Err(MonthError::InvalidMonth);
+impl Ord for Period {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // The longer period first when both start on the same day
+        self.first_day().cmp(&other.first_day()).then_with(|| other.last_day().cmp(&self.last_day()))
+    }
+}
+
+impl PartialOrd for Period {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<Maand> for Period {
+    fn from(maand: Maand) -> Self {
+        Period::Maand(maand)
+    }
+}
+
+impl From<Kwartaal> for Period {
+    fn from(kwartaal: Kwartaal) -> Self {
+        Period::Kwartaal(kwartaal)
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Period::Maand(maand) => maand.fmt(f),
+            Period::Kwartaal(kwartaal) => fmt::Display::fmt(kwartaal, f),
+            Period::Jaar(year) => year.fmt(f),
         }
+    }
+}
 
-        if month > 12 {
-            return Err(MonthError::InvalidMonth);
+impl FromStr for Period {
+    type Err = PeriodError;
+
+    /// Parses a year (`2023`), a quarter (`20233`), or a month (`202307`, `07/2023` or `7/2023`).
+    fn from_str(period: &str) -> Result<Self, Self::Err> {
+        let period = period.trim();
+
+        if period.contains('/') {
+            return Ok(Period::Maand(Maand::new(period.to_string())?));
         }
 
-        Ok(Self { year, month })
+        match period.len() {
+            4 => match period.parse::<u16>() {
+                Ok(year) if (1970..=2100).contains(&year) => Ok(Period::Jaar(year)),
+                _ => Err(PeriodError::InvalidPeriod(period.to_string())),
+            },
+            5 => Ok(Period::Kwartaal(Kwartaal::new(period.to_string())?)),
+            6 => Ok(Period::Maand(Maand::new(period.to_string())?)),
+            _ => Err(PeriodError::InvalidPeriod(period.to_string())),
+        }
     }
+}
 
-    pub fn to_kwartaal(&self) -> Kwartaal {
-        let quarter = match self.month {
-            1 | 2 | 3 => 1,
-            4 | 5 | 6 => 2,
-            7 | 8 | 9 => 3,
-            10 | 11 | 12 => 4,
-            _ => 0, // This can never happen
-        };
+#[derive(Error, Debug, PartialEq)]
+pub enum MoneyError {
+    #[error("Invalid amount {0}.")]
+    InvalidAmount(String),
+    #[error("Amount {0} is out of range.")]
+    Overflow(String),
+}
+
+/// An amount in euro, kept as a whole number of cents so sums do not drift.
+///
+/// Amounts with more than two decimals (e.g. the float noise `515.530000000001` of Excel) are rounded to the
+/// cent, half away from zero, on their decimal notation : `0.005` becomes `0.01` and `-0.005` becomes `-0.01`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money {
+    cents: i64,
+}
 
-        Kwartaal { year: self.year, quarter }
+impl Money {
+    pub const ZERO: Money = Money { cents: 0 };
+
+    pub fn from_cents(cents: i64) -> Self {
+        Money { cents }
+    }
+
+    pub fn cents(&self) -> i64 {
+        self.cents
+    }
+
+    /// Converts a floating point amount, e.g. a numeric Excel cell, rounding as described above.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MoneyError::InvalidAmount` if the amount is not finite, and `MoneyError::Overflow` if it does
+    /// not fit.
+    pub fn from_f64(bedrag: f64) -> Result<Self, MoneyError> {
+        if !bedrag.is_finite() {
+            return Err(MoneyError::InvalidAmount(bedrag.to_string()));
+        }
+        // The shortest notation that reads back as the same float, so 0.1 is "0.1" and not 0.1000000000000000055...
+        parse_decimal(&format!("{bedrag}"))
+    }
+
+    /// The amount as a float, e.g. to write it to a numeric Excel cell.
+    pub fn to_f64(&self) -> f64 {
+        self.cents as f64 / 100.0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.cents == 0
+    }
+
+    pub fn abs(&self) -> Self {
+        Money { cents: self.cents.abs() }
+    }
+}
+
+impl FromStr for Money {
+    type Err = MoneyError;
+
+    /// Parses an amount in Dutch notation (thousands separator '.', decimal separator ',').
+    ///
+    /// Amounts without a comma are parsed as is, as numeric Excel cells are read with a decimal point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lvgl::Money;
+    ///
+    /// assert_eq!("1.234,56".parse::<Money>().unwrap(), Money::from_cents(123456));
+    /// assert_eq!("1234.56".parse::<Money>().unwrap(), Money::from_cents(123456));
+    /// assert_eq!("515.530000000001".parse::<Money>().unwrap(), Money::from_cents(51553));
+    /// ```
+    fn from_str(bedrag: &str) -> Result<Self, Self::Err> {
+        let bedrag = bedrag.trim().replace([' ', '\u{a0}', '€'], "");
+
+        if bedrag.contains(',') {
+            parse_decimal(&bedrag.replace('.', "").replace(',', "."))
+        } else if bedrag.contains(['e', 'E']) {
+            // Very small or large numeric cells, e.g. 1.5E-3
+            let float = bedrag.parse::<f64>().map_err(|_| MoneyError::InvalidAmount(bedrag.clone()))?;
+            Money::from_f64(float)
+        } else {
+            parse_decimal(&bedrag)
+        }
+    }
+}
+
+/// Parses a decimal number with a decimal point to cents.
+fn parse_decimal(bedrag: &str) -> Result<Money, MoneyError> {
+    let invalid = || MoneyError::InvalidAmount(bedrag.to_string());
+    let overflow = || MoneyError::Overflow(bedrag.to_string());
+
+    let (negative, digits) = match bedrag.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, bedrag.strip_prefix('+').unwrap_or(bedrag)),
+    };
+    let (euros, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if euros.is_empty() && fraction.is_empty() {
+        return Err(invalid());
+    }
+    if !euros.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+
+    let digit = |index: usize| fraction.as_bytes().get(index).map_or(0, |b| (b - b'0') as i64);
+    let euros = if euros.is_empty() { 0 } else { euros.parse::<i64>().map_err(|_| overflow())? };
+    let mut cents = euros.checked_mul(100).and_then(|cents| cents.checked_add(digit(0) * 10 + digit(1))).ok_or_else(overflow)?;
+    if digit(2) >= 5 {
+        cents += 1;
+    }
+
+    Ok(Money { cents: if negative { -cents } else { cents } })
+}
+
+impl fmt::Display for Money {
+    /// Always two decimals and a decimal point, e.g. `-1234.50`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.cents < 0 { "-" } else { "" };
+        let cents = self.cents.unsigned_abs();
+        f.pad(&format!("{sign}{}.{:02}", cents / 100, cents % 100))
+    }
+}
+
+impl std::ops::Add for Money {
+    type Output = Money;
+
+    fn add(self, other: Money) -> Money {
+        Money { cents: self.cents + other.cents }
+    }
+}
+
+impl std::ops::Sub for Money {
+    type Output = Money;
+
+    fn sub(self, other: Money) -> Money {
+        Money { cents: self.cents - other.cents }
+    }
+}
+
+impl std::ops::Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money { cents: -self.cents }
+    }
+}
+
+impl std::ops::AddAssign for Money {
+    fn add_assign(&mut self, other: Money) {
+        self.cents += other.cents;
+    }
+}
+
+impl std::ops::SubAssign for Money {
+    fn sub_assign(&mut self, other: Money) {
+        self.cents -= other.cents;
+    }
+}
+
+impl std::iter::Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, |total, bedrag| total + bedrag)
+    }
+}
+
+impl<'a> std::iter::Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Money {
+        iter.copied().sum()
     }
 }
 
@@ -305,6 +963,8 @@ mod tests {
             let result = rrn.check();
             assert!(result.is_ok());
             assert_eq!(result.unwrap(), Gender::F);
+            assert_eq!(Gender::F.to_string(), "V");
+            assert_eq!(Gender::M.to_string(), "M");
         }
 
         #[test]
@@ -338,6 +998,22 @@ mod tests {
             assert_eq!(rrn.unwrap_err(), RrnError::InvalidLength);
         }
 
+        #[test]
+        fn test_rrn_invalid_character() {
+            assert_eq!(Rrn::new("abcdefghijk").unwrap_err(), RrnError::InvalidCharacter);
+            assert_eq!(Rrn::new("6910013635X").unwrap_err(), RrnError::InvalidCharacter);
+            // Non-ASCII input has more bytes than characters, it must not reach the slicing of `info`
+            assert_eq!(Rrn::new("éééééa").unwrap_err(), RrnError::InvalidCharacter);
+            assert_eq!(Rrn::new("69100136３59").unwrap_err(), RrnError::InvalidCharacter);
+        }
+
+        #[test]
+        fn test_rrn_leading_zeros_dropped() {
+            let rrn1 = Rrn::new("11820963").unwrap();
+            let rrn2 = Rrn::new("00011820963").unwrap();
+            assert_eq!(rrn1, rrn2);
+        }
+
         #[test]
         fn test_rrn_invalid_length_too_short() {
             let rrn = Rrn::new("1234678");
@@ -366,6 +1042,95 @@ mod tests {
             let rrn2 = Rrn::new("95022899874").unwrap();
             assert_ne!(rrn1, rrn2);
         }
+
+        #[test]
+        fn test_rrn_info_pre2000() {
+            let info = Rrn::new("69.10.01-363.59").unwrap().info().unwrap();
+            assert_eq!(info.kind, RrnKind::Rijksregister);
+            assert_eq!(info.birth_year, 1969);
+            assert_eq!(info.birth_date, Some(Date::from_calendar_date(1969, Month::October, 1).unwrap()));
+            assert_eq!(info.gender, Some(Gender::M));
+            assert_eq!(info.sequence, 363);
+        }
+
+        #[test]
+        fn test_rrn_info_post2000() {
+            let info = Rrn::new("05051200201").unwrap().info().unwrap();
+            assert_eq!(info.birth_year, 2005);
+            assert_eq!(info.birth_date, Some(Date::from_calendar_date(2005, Month::May, 12).unwrap()));
+            assert_eq!(info.gender, Some(Gender::F));
+        }
+
+        #[test]
+        fn test_rrn_info_bis() {
+            let info = Rrn::new("85431512358").unwrap().info().unwrap();
+            assert_eq!(info.kind, RrnKind::Bis { gender_known: true });
+            assert_eq!(info.birth_date, Some(Date::from_calendar_date(1985, Month::March, 15).unwrap()));
+            assert_eq!(info.gender, Some(Gender::M));
+
+            let rrn = Rrn::new("85231512414").unwrap();
+            let info = rrn.info().unwrap();
+            assert_eq!(info.kind, RrnKind::Bis { gender_known: false });
+            assert_eq!(info.birth_date, Some(Date::from_calendar_date(1985, Month::March, 15).unwrap()));
+            assert_eq!(info.gender, None);
+            assert_eq!(rrn.check().unwrap_err(), RrnError::UnknownGender);
+        }
+
+        #[test]
+        fn test_rrn_info_unknown_birth_date() {
+            let info = Rrn::new("85000012313").unwrap().info().unwrap();
+            assert_eq!(info.kind, RrnKind::Rijksregister);
+            assert_eq!(info.birth_year, 1985);
+            assert_eq!(info.birth_date, None);
+
+            let info = Rrn::new("85430012323").unwrap().info().unwrap();
+            assert_eq!(info.kind, RrnKind::Bis { gender_known: true });
+            assert_eq!(info.birth_date, None);
+        }
+
+        #[test]
+        fn test_rrn_info_invalid_date() {
+            assert_eq!(Rrn::new("85131500167").unwrap().info().unwrap_err(), RrnError::InvalidDate);
+            assert_eq!(Rrn::new("85133200142").unwrap().info().unwrap_err(), RrnError::InvalidDate);
+        }
+    }
+
+    mod kbo_tests {
+        use super::*;
+
+        #[test]
+        fn test_kbo_valid() {
+            let kbo = Kbo::new("207527540").unwrap();
+            assert_eq!(kbo.as_str(), "0207527540");
+            assert_eq!(kbo, Kbo::new("0207.527.540").unwrap());
+            assert_eq!(kbo, Kbo::new("BE 0207.527.540").unwrap());
+            assert_eq!(kbo, Kbo::new("be0207527540").unwrap());
+        }
+
+        #[test]
+        fn test_kbo_display() {
+            let kbo = Kbo::new("208322049").unwrap();
+            assert_eq!(kbo.to_string(), "0208.322.049");
+            assert_eq!(kbo.to_btw(), "BE0208322049");
+        }
+
+        #[test]
+        fn test_kbo_invalid_length() {
+            assert_eq!(Kbo::new("20752754").unwrap_err(), KboError::InvalidLength);
+            assert_eq!(Kbo::new("02075275400").unwrap_err(), KboError::InvalidLength);
+            assert_eq!(Kbo::new("").unwrap_err(), KboError::InvalidLength);
+            assert_eq!(Kbo::new("20752754O").unwrap_err(), KboError::InvalidLength);
+        }
+
+        #[test]
+        fn test_kbo_invalid_first_digit() {
+            assert_eq!(Kbo::new("2207527540").unwrap_err(), KboError::InvalidFirstDigit);
+        }
+
+        #[test]
+        fn test_kbo_invalid_control() {
+            assert_eq!(Kbo::new("207527541").unwrap_err(), KboError::InvalidControl);
+        }
     }
 
     mod gender_tests {
@@ -441,6 +1206,77 @@ mod tests {
             let kwartaal = Kwartaal::new("20211".to_string()).unwrap();
             assert_eq!(format!("{:?}", kwartaal), "20211");
         }
+
+        #[test]
+        fn test_kwartaal_display_and_from_str() {
+            let kwartaal: Kwartaal = "20233".parse().unwrap();
+            assert_eq!(kwartaal.to_string(), "20233");
+            assert_eq!(format!("{:>7}", kwartaal), "  20233");
+        }
+
+        #[test]
+        fn test_kwartaal_ord() {
+            let mut kwartalen: Vec<Kwartaal> = ["20231", "20224", "20232"].iter().map(|k| k.parse().unwrap()).collect();
+            kwartalen.sort();
+            assert_eq!(format!("{:?}", kwartalen), "[20224, 20231, 20232]");
+        }
+
+        #[test]
+        fn test_kwartaal_next_previous() {
+            let kwartaal: Kwartaal = "20234".parse().unwrap();
+            assert_eq!(kwartaal.next().to_string(), "20241");
+            assert_eq!(kwartaal.previous().to_string(), "20233");
+            assert_eq!(kwartaal.next().previous(), kwartaal);
+        }
+
+        #[test]
+        fn test_kwartaal_days_and_months() {
+            let kwartaal: Kwartaal = "20241".parse().unwrap();
+            assert_eq!(kwartaal.first_day(), Date::from_calendar_date(2024, Month::January, 1).unwrap());
+            assert_eq!(kwartaal.last_day(), Date::from_calendar_date(2024, Month::March, 31).unwrap());
+            assert_eq!(kwartaal.months(), 1..=3);
+            assert!(kwartaal.contains_month(2024, 3));
+            assert!(!kwartaal.contains_month(2024, 4));
+            assert!(!kwartaal.contains_month(2023, 1));
+            assert_eq!(Kwartaal::from_date(Date::from_calendar_date(2023, Month::November, 30).unwrap()).to_string(), "20234");
+        }
+    }
+
+    mod kwartaal_range_tests {
+        use super::*;
+
+        #[test]
+        fn test_kwartaal_range_from_str() {
+            let range: KwartaalRange = "20213_20242".parse().unwrap();
+            assert_eq!(range.start.to_string(), "20213");
+            assert_eq!(range.stop.to_string(), "20242");
+            assert_eq!(range.to_string(), "20213_20242");
+            assert_eq!(range.len(), 12);
+        }
+
+        #[test]
+        fn test_kwartaal_range_iter() {
+            let range: KwartaalRange = "20233_20242".parse().unwrap();
+            let kwartalen: Vec<String> = range.iter().map(|kwartaal| kwartaal.to_string()).collect();
+            assert_eq!(kwartalen, ["20233", "20234", "20241", "20242"]);
+            assert!(range.contains(&"20241".parse().unwrap()));
+            assert!(!range.contains(&"20243".parse().unwrap()));
+        }
+
+        #[test]
+        fn test_kwartaal_range_invalid() {
+            assert_eq!("20242_20213".parse::<KwartaalRange>().unwrap_err(), KwartaalError::InvalidRange);
+            assert_eq!("20213".parse::<KwartaalRange>().unwrap_err(), KwartaalError::InvalidLength);
+            assert_eq!("20213_20245".parse::<KwartaalRange>().unwrap_err(), KwartaalError::InvalidQuarter);
+        }
+
+        #[test]
+        fn test_kwartaal_range_missing() {
+            let range: KwartaalRange = "20231_20234".parse().unwrap();
+            let present: Vec<Kwartaal> = vec!["20232".parse().unwrap(), "20233".parse().unwrap()];
+            let missing: Vec<String> = range.missing(&present).iter().map(Kwartaal::to_string).collect();
+            assert_eq!(missing, ["20231", "20234"]);
+        }
     }
 
     mod bosa_month_tests {
@@ -564,4 +1400,164 @@ mod tests {
             assert_eq!(kwartaal.quarter, 1);
         }
     }
-}
\ No newline at end of file
+
+    mod maand_tests {
+        use super::*;
+
+        #[test]
+        fn test_maand_same_month_in_all_encodings() {
+            let month = Maand::new("202307".to_string()).unwrap();
+            assert_eq!(month, Maand::new("07/2023".to_string()).unwrap());
+            assert_eq!(month, Maand::new("7/2023".to_string()).unwrap());
+            assert_eq!(month, Maand::from_jaar_maand("2023.0", "7.0").unwrap());
+            assert_eq!(month, Maand::from_jaar_maand("2023", "7").unwrap());
+            assert_eq!(month.to_string(), "202307");
+        }
+
+        #[test]
+        fn test_maand_from_jaar_maand_invalid() {
+            assert_eq!(Maand::from_jaar_maand("2023.0", "13.0").unwrap_err(), MonthError::InvalidMonth);
+            assert!(Maand::from_jaar_maand("", "1.0").is_err());
+        }
+
+        #[test]
+        fn test_maand_days() {
+            let month = Maand::new("202402".to_string()).unwrap();
+            assert_eq!(month.first_day(), Date::from_calendar_date(2024, Month::February, 1).unwrap());
+            assert_eq!(month.last_day(), Date::from_calendar_date(2024, Month::February, 29).unwrap());
+        }
+
+        #[test]
+        fn test_maand_ord() {
+            let mut months: Vec<Maand> = ["1/2024", "202312", "11/2023"].iter().map(|m| m.parse().unwrap()).collect();
+            months.sort();
+            assert_eq!(months.iter().map(|m| m.to_string()).collect::<Vec<_>>(), ["202311", "202312", "202401"]);
+        }
+    }
+
+    mod period_tests {
+        use super::*;
+
+        #[test]
+        fn test_period_from_str() {
+            assert_eq!("2023".parse::<Period>().unwrap(), Period::Jaar(2023));
+            assert_eq!("20233".parse::<Period>().unwrap(), Period::Kwartaal(Kwartaal { year: 2023, quarter: 3 }));
+            assert_eq!("202307".parse::<Period>().unwrap(), Period::Maand(Maand { year: 2023, month: 7 }));
+            assert_eq!("7/2023".parse::<Period>().unwrap(), Period::Maand(Maand { year: 2023, month: 7 }));
+            assert_eq!("07/2023".parse::<Period>().unwrap(), Period::Maand(Maand { year: 2023, month: 7 }));
+        }
+
+        #[test]
+        fn test_period_from_str_invalid() {
+            assert_eq!("1800".parse::<Period>().unwrap_err(), PeriodError::InvalidPeriod("1800".to_string()));
+            assert_eq!("2023071".parse::<Period>().unwrap_err(), PeriodError::InvalidPeriod("2023071".to_string()));
+            assert_eq!("20235".parse::<Period>().unwrap_err(), PeriodError::KwartaalError(KwartaalError::InvalidQuarter));
+            assert_eq!("13/2023".parse::<Period>().unwrap_err(), PeriodError::MonthError(MonthError::InvalidMonth));
+        }
+
+        #[test]
+        fn test_period_display() {
+            for period in ["2023", "20233", "202307"] {
+                assert_eq!(period.parse::<Period>().unwrap().to_string(), period);
+            }
+        }
+
+        #[test]
+        fn test_period_to() {
+            let month = Period::from(Maand { year: 2023, month: 11 });
+            assert_eq!(month.to(Granularity::Maand).unwrap(), month);
+            assert_eq!(month.to(Granularity::Kwartaal).unwrap(), Period::Kwartaal(Kwartaal { year: 2023, quarter: 4 }));
+            assert_eq!(month.to(Granularity::Jaar).unwrap(), Period::Jaar(2023));
+
+            let year = Period::Jaar(2023);
+            assert_eq!(
+                year.to(Granularity::Kwartaal).unwrap_err(),
+                PeriodError::InvalidGranularity { from: Granularity::Jaar, to: Granularity::Kwartaal }
+            );
+        }
+
+        #[test]
+        fn test_period_contains() {
+            let quarter: Period = "20234".parse().unwrap();
+            assert!(quarter.contains(&"11/2023".parse().unwrap()));
+            assert!(!quarter.contains(&"9/2023".parse().unwrap()));
+            assert!(Period::Jaar(2023).contains(&quarter));
+            assert!(!quarter.contains(&Period::Jaar(2023)));
+        }
+
+        #[test]
+        fn test_period_ord() {
+            let mut periods: Vec<Period> = ["202304", "2023", "20232", "20231", "202212"].iter().map(|p| p.parse().unwrap()).collect();
+            periods.sort();
+            let sorted: Vec<String> = periods.iter().map(|p| p.to_string()).collect();
+            assert_eq!(sorted, ["202212", "2023", "20231", "20232", "202304"]);
+        }
+    }
+
+    mod money_tests {
+        use super::*;
+
+        fn money(bedrag: &str) -> Money {
+            bedrag.parse().unwrap()
+        }
+
+        #[test]
+        fn test_money_dutch() {
+            assert_eq!(money("1.234,56"), Money::from_cents(123456));
+            assert_eq!(money("-811,48"), Money::from_cents(-81148));
+            assert_eq!(money("1.000.000,00 €"), Money::from_cents(100000000));
+            assert_eq!(money("0,5"), Money::from_cents(50));
+        }
+
+        #[test]
+        fn test_money_numeric() {
+            assert_eq!(money("6251.05"), Money::from_cents(625105));
+            assert_eq!(money("755"), Money::from_cents(75500));
+            assert_eq!(money("-.5"), Money::from_cents(-50));
+            assert_eq!(money("1.5E-2"), Money::from_cents(2));
+        }
+
+        #[test]
+        fn test_money_float_noise() {
+            assert_eq!(money("515.530000000001"), Money::from_cents(51553));
+            assert_eq!(money("4285.1999999999998"), Money::from_cents(428520));
+            assert_eq!(Money::from_f64(0.1 + 0.2).unwrap(), Money::from_cents(30));
+        }
+
+        #[test]
+        fn test_money_rounding() {
+            assert_eq!(money("0.005"), Money::from_cents(1));
+            assert_eq!(money("0.0049"), Money::ZERO);
+            assert_eq!(money("-0.005"), Money::from_cents(-1));
+            assert_eq!(money("1,235"), Money::from_cents(124));
+            assert_eq!(Money::from_f64(1.005).unwrap(), Money::from_cents(101));
+        }
+
+        #[test]
+        fn test_money_invalid() {
+            assert_eq!("abc".parse::<Money>().unwrap_err(), MoneyError::InvalidAmount("abc".to_string()));
+            assert!("".parse::<Money>().is_err());
+            assert!("-".parse::<Money>().is_err());
+            assert!("1.2.3".parse::<Money>().is_err());
+            assert!(Money::from_f64(f64::NAN).is_err());
+            assert_eq!(
+                "99999999999999999999".parse::<Money>().unwrap_err(),
+                MoneyError::Overflow("99999999999999999999".to_string())
+            );
+        }
+
+        #[test]
+        fn test_money_sum_without_drift() {
+            let bedragen = vec![money("0.10"); 1000];
+            assert_eq!(bedragen.iter().sum::<Money>(), Money::from_cents(10000));
+            assert_eq!(money("6956.34") - money("6856.35"), money("99.99"));
+        }
+
+        #[test]
+        fn test_money_display() {
+            assert_eq!(Money::from_cents(-123450).to_string(), "-1234.50");
+            assert_eq!(Money::from_cents(-5).to_string(), "-0.05");
+            assert_eq!(format!("{:>8}", Money::from_cents(705)), "    7.05");
+        }
+    }
+}
@@ -0,0 +1,519 @@
+// Streaming reader for the data sheets of an xlsx workbook.
+// umya_spreadsheet loads the whole workbook (styles, themes, every cell) in memory before the first value
+// can be read. The DMFA, CIPAL and BOSA readers only need the cell values, row by row, so this reader
+// unzips the worksheet XML on the fly and yields one row at a time.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use thiserror::Error;
+use zip::ZipArchive;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum XlsxError {
+    #[error("File not found.")]
+    FileNotFound,
+    #[error("Invalid xlsx file: {0}")]
+    InvalidXlsx(String),
+    #[error("Sheet {0} not found.")]
+    SheetNotFound(String),
+}
+
+impl From<zip::result::ZipError> for XlsxError {
+    fn from(error: zip::result::ZipError) -> Self {
+        XlsxError::InvalidXlsx(error.to_string())
+    }
+}
+
+impl From<quick_xml::Error> for XlsxError {
+    fn from(error: quick_xml::Error) -> Self {
+        XlsxError::InvalidXlsx(error.to_string())
+    }
+}
+
+/// An xlsx workbook opened for streaming.
+///
+/// Only the sheet names and the shared strings are read when the workbook is opened, the rows of a sheet
+/// are read when its iterator is advanced.
+pub struct XlsxReader {
+    archive: ZipArchive<BufReader<File>>,
+    sheets: Vec<(String, String)>,
+    shared_strings: Vec<String>,
+}
+
+impl XlsxReader {
+    /// Opens an xlsx workbook.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the xlsx file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `XlsxError::FileNotFound` if the file can not be opened, and `XlsxError::InvalidXlsx` if it is
+    /// not a valid xlsx workbook.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lvgl::XlsxReader;
+    ///
+    /// let workbook = XlsxReader::open("tests/fixtures/207527540-cipal.xlsx").unwrap();
+    /// assert_eq!(workbook.sheet_names(), vec!["Data", "Info"]);
+    /// ```
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, XlsxError> {
+        let file = File::open(path).map_err(|_| XlsxError::FileNotFound)?;
+        let mut archive = ZipArchive::new(BufReader::new(file))?;
+
+        let relations = read_relations(&mut archive)?;
+        let sheets = read_sheets(&mut archive)?
+            .into_iter()
+            .map(|(name, id)| {
+                let target = relations.get(&id)
+                    .ok_or_else(|| XlsxError::InvalidXlsx(format!("no relation for sheet {}", name)))?;
+                Ok((name, part_name(target)))
+            })
+            .collect::<Result<_, XlsxError>>()?;
+        let shared_strings = read_shared_strings(&mut archive)?;
+
+        Ok(XlsxReader { archive, sheets, shared_strings })
+    }
+
+    /// The names of the sheets, in workbook order.
+    pub fn sheet_names(&self) -> Vec<&str> {
+        self.sheets.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// Streams the rows of the sheet at `index`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `XlsxError::SheetNotFound` if there is no sheet at `index`.
+    pub fn sheet(&mut self, index: usize) -> Result<Rows<'_>, XlsxError> {
+        let (_, part) = self.sheets.get(index).ok_or_else(|| XlsxError::SheetNotFound(index.to_string()))?;
+        let file = self.archive.by_name(part)?;
+        Ok(Rows::new(BufReader::new(file), &self.shared_strings))
+    }
+
+    /// Streams the rows of the sheet called `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `XlsxError::SheetNotFound` if there is no sheet called `name`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lvgl::XlsxReader;
+    ///
+    /// let mut workbook = XlsxReader::open("tests/fixtures/207527540-cipal.xlsx").unwrap();
+    /// let header = workbook.sheet_by_name("Data").unwrap().next().unwrap().unwrap();
+    /// assert_eq!(header.get(1), "naam");
+    /// ```
+    pub fn sheet_by_name(&mut self, name: &str) -> Result<Rows<'_>, XlsxError> {
+        let index = self.sheets.iter()
+            .position(|(sheet, _)| sheet == name)
+            .ok_or_else(|| XlsxError::SheetNotFound(name.to_string()))?;
+        self.sheet(index)
+    }
+}
+
+/// One row of a sheet, with the values as umya_spreadsheet's `get_value` would return them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    /// The 1-based row number.
+    pub number: u32,
+    cells: Vec<String>,
+}
+
+impl Row {
+    /// The value of the cell in the 1-based `column`, or an empty string if the cell is empty.
+    pub fn get(&self, column: u32) -> &str {
+        column.checked_sub(1)
+            .and_then(|index| self.cells.get(index as usize))
+            .map_or("", String::as_str)
+    }
+
+    /// The non-empty cells of the row as (1-based column, value) pairs.
+    pub fn cells(&self) -> impl Iterator<Item = (u32, &str)> {
+        self.cells.iter()
+            .enumerate()
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(index, value)| (index as u32 + 1, value.as_str()))
+    }
+}
+
+/// Iterator over the rows of a sheet, see `XlsxReader::sheet`.
+pub struct Rows<'a> {
+    reader: Reader<Box<dyn BufRead + 'a>>,
+    shared_strings: &'a [String],
+    buf: Vec<u8>,
+    last_row: u32,
+    done: bool,
+}
+
+/// The type of a cell, from its `t` attribute.
+#[derive(Clone, Copy, PartialEq)]
+enum CellType {
+    Number,
+    SharedString,
+    Boolean,
+    Text,
+}
+
+impl<'a> Rows<'a> {
+    fn new<R: BufRead + 'a>(reader: R, shared_strings: &'a [String]) -> Self {
+        Rows {
+            reader: Reader::from_reader(Box::new(reader)),
+            shared_strings,
+            buf: Vec::new(),
+            last_row: 0,
+            done: false,
+        }
+    }
+
+    fn next_row(&mut self) -> Result<Option<Row>, XlsxError> {
+        let mut row: Option<Row> = None;
+        let mut column = 0;
+        let mut cell_type = CellType::Number;
+        let mut value = String::new();
+        let mut in_value = false;
+
+        loop {
+            self.buf.clear();
+            match self.reader.read_event_into(&mut self.buf)? {
+                Event::Empty(e) if e.local_name().as_ref() == b"row" => {
+                    return Ok(Some(Row { number: row_number(&e, &mut self.last_row)?, cells: Vec::new() }));
+                }
+                Event::Start(e) if e.local_name().as_ref() == b"row" => {
+                    row = Some(Row { number: row_number(&e, &mut self.last_row)?, cells: Vec::new() });
+                    column = 0;
+                }
+                Event::Empty(e) if e.local_name().as_ref() == b"c" => {
+                    (column, _) = cell(&e, column)?;
+                }
+                Event::Start(e) if e.local_name().as_ref() == b"c" => {
+                    (column, cell_type) = cell(&e, column)?;
+                    value.clear();
+                }
+                Event::Start(e) if matches!(e.local_name().as_ref(), b"v" | b"t") => in_value = true,
+                Event::End(e) if matches!(e.local_name().as_ref(), b"v" | b"t") => in_value = false,
+                Event::Text(text) if in_value => {
+                    value.push_str(&text.unescape()?);
+                }
+                Event::CData(text) if in_value => {
+                    value.push_str(&String::from_utf8_lossy(&text));
+                }
+                Event::End(e) if e.local_name().as_ref() == b"c" => {
+                    if let Some(row) = row.as_mut() {
+                        let value = cell_value(self.shared_strings, cell_type, &value)?;
+                        if !value.is_empty() {
+                            let index = column as usize - 1;
+                            if row.cells.len() <= index {
+                                row.cells.resize(index + 1, String::new());
+                            }
+                            row.cells[index] = value;
+                        }
+                    }
+                }
+                Event::End(e) if e.local_name().as_ref() == b"row" => return Ok(row),
+                Event::End(e) if e.local_name().as_ref() == b"sheetData" => return Ok(None),
+                Event::Eof => return Ok(None),
+                _ => (),
+            }
+        }
+    }
+}
+
+impl Iterator for Rows<'_> {
+    type Item = Result<Row, XlsxError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let row = self.next_row().transpose();
+        if !matches!(row, Some(Ok(_))) {
+            self.done = true;
+        }
+        row
+    }
+}
+
+/// The number of a `<row>` element, from its `r` attribute or else the row after `last_row`.
+fn row_number(e: &BytesStart, last_row: &mut u32) -> Result<u32, XlsxError> {
+    let number = match attribute(e, b"r")? {
+        Some(r) => r.parse().map_err(|_| XlsxError::InvalidXlsx(format!("invalid row {}", r)))?,
+        None => *last_row + 1,
+    };
+    *last_row = number;
+    Ok(number)
+}
+
+/// The value of a cell as umya_spreadsheet shows it.
+fn cell_value(shared_strings: &[String], cell_type: CellType, value: &str) -> Result<String, XlsxError> {
+    Ok(match cell_type {
+        CellType::SharedString => {
+            let index: usize = value.trim().parse()
+                .map_err(|_| XlsxError::InvalidXlsx(format!("invalid shared string {}", value)))?;
+            shared_strings.get(index)
+                .cloned()
+                .ok_or_else(|| XlsxError::InvalidXlsx(format!("invalid shared string {}", value)))?
+        }
+        CellType::Boolean => match value {
+            "1" => "TRUE".to_string(),
+            _ => "FALSE".to_string(),
+        },
+        CellType::Text => value.to_string(),
+        // Numbers are shown the way umya_spreadsheet shows them, e.g. "2.0" as "2"
+        CellType::Number if value.contains(['.', 'e', 'E']) => match value.parse::<f64>() {
+            Ok(number) => number.to_string(),
+            Err(_) => value.to_string(),
+        },
+        CellType::Number => value.to_string(),
+    })
+}
+
+/// The unescaped value of the attribute with the local name `name`.
+fn attribute(e: &BytesStart, name: &[u8]) -> Result<Option<String>, XlsxError> {
+    for attribute in e.attributes() {
+        let attribute = attribute.map_err(|error| XlsxError::InvalidXlsx(error.to_string()))?;
+        if attribute.key.local_name().as_ref() == name {
+            return Ok(Some(attribute.unescape_value()?.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/// The 1-based column and the type of a `<c>` element. Without a reference (e.g. "AB12") the cell is the
+/// one after `previous`.
+fn cell(e: &BytesStart, previous: u32) -> Result<(u32, CellType), XlsxError> {
+    let mut column = previous + 1;
+    let mut cell_type = CellType::Number;
+    for attribute in e.attributes().with_checks(false) {
+        let attribute = attribute.map_err(|error| XlsxError::InvalidXlsx(error.to_string()))?;
+        match attribute.key.local_name().as_ref() {
+            b"r" => {
+                column = column_index(&attribute.value).ok_or_else(|| {
+                    XlsxError::InvalidXlsx(format!("invalid cell reference {}", String::from_utf8_lossy(&attribute.value)))
+                })?;
+            }
+            b"t" => {
+                cell_type = match attribute.value.as_ref() {
+                    b"s" => CellType::SharedString,
+                    b"b" => CellType::Boolean,
+                    b"str" | b"inlineStr" | b"e" => CellType::Text,
+                    _ => CellType::Number,
+                };
+            }
+            _ => (),
+        }
+    }
+    Ok((column, cell_type))
+}
+
+/// Converts the letters of a cell reference to a 1-based column, e.g. "A1" -> 1 and "AB12" -> 28.
+fn column_index(reference: &[u8]) -> Option<u32> {
+    let letters = reference.iter().take_while(|byte| byte.is_ascii_alphabetic()).count();
+    if letters == 0 || letters > 3 {
+        return None;
+    }
+    Some(reference[..letters].iter().fold(0, |column, letter| column * 26 + (letter.to_ascii_uppercase() - b'A' + 1) as u32))
+}
+
+/// The A1 reference of a cell, from its 1-based column and row, e.g. (28, 12) -> "AB12".
+///
+/// # Examples
+///
+/// ```
+/// use lvgl::cell_reference;
+///
+/// assert_eq!(cell_reference(1, 1), "A1");
+/// assert_eq!(cell_reference(28, 12), "AB12");
+/// ```
+pub fn cell_reference(column: u32, row: u32) -> String {
+    let mut letters = Vec::new();
+    let mut column = column;
+    while column > 0 {
+        letters.push(b'A' + ((column - 1) % 26) as u8);
+        column = (column - 1) / 26;
+    }
+    letters.reverse();
+    format!("{}{}", String::from_utf8(letters).unwrap_or_default(), row)
+}
+
+/// Resolves a relationship target of the workbook to its name in the archive.
+fn part_name(target: &str) -> String {
+    match target.strip_prefix('/') {
+        Some(absolute) => absolute.to_string(),
+        None => format!("xl/{}", target),
+    }
+}
+
+fn xml_reader<'a, R: Read + std::io::Seek>(archive: &'a mut ZipArchive<R>, name: &str) -> Result<Option<Reader<BufReader<zip::read::ZipFile<'a>>>>, XlsxError> {
+    match archive.by_name(name) {
+        Ok(file) => Ok(Some(Reader::from_reader(BufReader::new(file)))),
+        Err(zip::result::ZipError::FileNotFound) => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Maps the relationship ids of the workbook to their targets.
+fn read_relations<R: Read + std::io::Seek>(archive: &mut ZipArchive<R>) -> Result<HashMap<String, String>, XlsxError> {
+    let mut reader = xml_reader(archive, "xl/_rels/workbook.xml.rels")?
+        .ok_or_else(|| XlsxError::InvalidXlsx("xl/_rels/workbook.xml.rels not found".to_string()))?;
+    let mut relations = HashMap::new();
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"Relationship" => {
+                if let (Some(id), Some(target)) = (attribute(&e, b"Id")?, attribute(&e, b"Target")?) {
+                    relations.insert(id, target);
+                }
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+    Ok(relations)
+}
+
+/// The (name, relationship id) of the sheets in the workbook.
+fn read_sheets<R: Read + std::io::Seek>(archive: &mut ZipArchive<R>) -> Result<Vec<(String, String)>, XlsxError> {
+    let mut reader = xml_reader(archive, "xl/workbook.xml")?
+        .ok_or_else(|| XlsxError::InvalidXlsx("xl/workbook.xml not found".to_string()))?;
+    let mut sheets = Vec::new();
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"sheet" => {
+                if let (Some(name), Some(id)) = (attribute(&e, b"name")?, attribute(&e, b"id")?) {
+                    sheets.push((name, id));
+                }
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+    Ok(sheets)
+}
+
+/// Reads the shared strings table; rich text runs are concatenated and phonetic runs are skipped.
+fn read_shared_strings<R: Read + std::io::Seek>(archive: &mut ZipArchive<R>) -> Result<Vec<String>, XlsxError> {
+    let Some(mut reader) = xml_reader(archive, "xl/sharedStrings.xml")? else {
+        return Ok(Vec::new());
+    };
+    let mut strings = Vec::new();
+    let mut current = String::new();
+    let mut in_text = false;
+    let mut in_phonetic = false;
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"si" => current.clear(),
+                b"t" => in_text = !in_phonetic,
+                b"rPh" => in_phonetic = true,
+                _ => (),
+            },
+            Event::Empty(e) if e.local_name().as_ref() == b"si" => strings.push(String::new()),
+            Event::End(e) => match e.local_name().as_ref() {
+                b"si" => strings.push(std::mem::take(&mut current)),
+                b"t" => in_text = false,
+                b"rPh" => in_phonetic = false,
+                _ => (),
+            },
+            Event::Text(text) if in_text => current.push_str(&text.unescape()?),
+            Event::CData(text) if in_text => current.push_str(&String::from_utf8_lossy(&text)),
+            Event::Eof => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+    Ok(strings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use umya_spreadsheet::reader::xlsx;
+
+    /// Compares every value of every sheet with the values umya_spreadsheet reads.
+    fn assert_same_as_umya(filename: &str) {
+        let book = xlsx::read(filename).unwrap();
+        let mut workbook = XlsxReader::open(filename).unwrap();
+        assert_eq!(workbook.sheet_names().len(), book.get_sheet_count());
+
+        for index in 0..book.get_sheet_count() {
+            let sheet = book.get_sheet(&index).unwrap();
+            assert_eq!(workbook.sheet_names()[index], sheet.get_name());
+
+            let mut rows = 0;
+            for row in workbook.sheet(index).unwrap() {
+                let row = row.unwrap();
+                for column in 1..=sheet.get_highest_column() {
+                    assert_eq!(row.get(column), sheet.get_value((column, row.number)), "{}{}", column, row.number);
+                }
+                rows = row.number;
+            }
+            assert_eq!(rows, sheet.get_highest_row());
+        }
+    }
+
+    #[test]
+    fn test_same_as_umya_dmfa() {
+        assert_same_as_umya("tests/fixtures/207527540-dmfa.xlsx");
+        assert_same_as_umya("tests/fixtures/208322049-dmfa.xlsx");
+    }
+
+    #[test]
+    fn test_same_as_umya_cipal() {
+        assert_same_as_umya("tests/fixtures/207527540-cipal.xlsx");
+    }
+
+    #[test]
+    fn test_no_sheets() {
+        let mut workbook = XlsxReader::open("tests/fixtures/no_sheets.xlsx").unwrap();
+        assert!(workbook.sheet_names().is_empty());
+        assert_eq!(workbook.sheet(0).err(), Some(XlsxError::SheetNotFound("0".to_string())));
+    }
+
+    #[test]
+    fn test_open_errors() {
+        assert_eq!(XlsxReader::open("tests/fixtures/nonexistent.xlsx").err(), Some(XlsxError::FileNotFound));
+        assert!(matches!(XlsxReader::open("Cargo.toml").err(), Some(XlsxError::InvalidXlsx(_))));
+    }
+
+    #[test]
+    fn test_column_index() {
+        assert_eq!(column_index(b"A1"), Some(1));
+        assert_eq!(column_index(b"Z9"), Some(26));
+        assert_eq!(column_index(b"AB12"), Some(28));
+        assert_eq!(column_index(b"XFD1048576"), Some(16384));
+        assert_eq!(column_index(b"12"), None);
+    }
+
+    #[test]
+    fn test_cell_reference() {
+        for reference in ["A1", "Z9", "AA3", "AZ7", "XFD1048576"] {
+            let column = column_index(reference.as_bytes()).unwrap();
+            let row = reference.trim_start_matches(|c: char| c.is_ascii_alphabetic()).parse().unwrap();
+            assert_eq!(cell_reference(column, row), reference);
+        }
+    }
+
+    #[test]
+    fn test_row_get() {
+        let row = Row { number: 3, cells: vec!["a".to_string(), String::new(), "c".to_string()] };
+        assert_eq!(row.get(0), "");
+        assert_eq!(row.get(1), "a");
+        assert_eq!(row.get(2), "");
+        assert_eq!(row.get(4), "");
+        assert_eq!(row.cells().collect::<Vec<_>>(), vec![(1, "a"), (3, "c")]);
+    }
+}
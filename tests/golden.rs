@@ -0,0 +1,218 @@
+// Golden end-to-end tests : the whole pipeline (read, map, compare, write the verschil document) is run on the
+// fixtures and the written workbook is compared cell by cell with the verschil documents of the accountants. There
+// is no BOSA export among the fixtures, the BOSA path only has a smoke test.
+
+use std::collections::HashMap;
+use std::path::Path;
+use tempfile::{Builder, NamedTempFile};
+use umya_spreadsheet::reader::xlsx;
+use umya_spreadsheet::Worksheet;
+use lvgl::{compare_dmfa_bosa, compare_dmfa_cipal, DmfaReader, Mapping, Money, Privacy, PrivacyProfile, VerschilReport};
+
+/// Amounts may differ by a rounding error, not by a cent.
+const TOLERANCE: f64 = 0.005;
+
+/// Kwart, WGC, WNK, INSZ and LC of a difference line.
+type Key = (String, String, String, String, String);
+
+/// Loonbestand, DMFA and Verschil of a difference line.
+type Bedragen = (f64, f64, f64);
+
+/// Reads the difference lines of a verschil document : the rows below the header row with a Kwart in column A.
+fn verschil_lines(path: &Path) -> HashMap<Key, Bedragen> {
+    let book = xlsx::read(path).unwrap();
+    let sheet = book.get_sheet(&0).unwrap();
+
+    let header_row = (1..=sheet.get_highest_row())
+        .find(|row| sheet.get_value((1, *row)).trim().starts_with("Kwart"))
+        .unwrap();
+
+    let mut lines = HashMap::new();
+    for row in header_row + 1..=sheet.get_highest_row() {
+        let kwart = sheet.get_value((1, row)).trim().to_string();
+        if kwart.len() != 5 || !kwart.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+
+        let text = |column: u32| sheet.get_value((column, row)).trim().to_string();
+        let bedrag = |column: u32| text(column).parse::<f64>().unwrap();
+        let insz = format!("{:0>11}", text(4));
+
+        let key = (kwart, text(2), text(3), insz, text(5));
+        assert!(lines.insert(key, (bedrag(6), bedrag(7), bedrag(8))).is_none(), "duplicate line on row {row}");
+    }
+    lines
+}
+
+fn assert_same_lines(actual: &HashMap<Key, Bedragen>, expected: &HashMap<Key, Bedragen>) {
+    for (key, (loonbestand, dmfa, verschil)) in expected {
+        let (actual_loonbestand, actual_dmfa, actual_verschil) = actual.get(key)
+            .unwrap_or_else(|| panic!("missing line {key:?}"));
+        assert!((actual_loonbestand - loonbestand).abs() < TOLERANCE, "Loonbestand of {key:?}: {actual_loonbestand} != {loonbestand}");
+        assert!((actual_dmfa - dmfa).abs() < TOLERANCE, "DMFA of {key:?}: {actual_dmfa} != {dmfa}");
+        assert!((actual_verschil - verschil).abs() < TOLERANCE, "Verschil of {key:?}: {actual_verschil} != {verschil}");
+    }
+    for key in actual.keys() {
+        assert!(expected.contains_key(key), "unexpected line {key:?}");
+    }
+}
+
+/// The row of the total of the table, with "Totaal" in column G.
+fn totaal_row(sheet: &Worksheet) -> u32 {
+    (1..=sheet.get_highest_row()).find(|row| sheet.get_value((7, *row)) == "Totaal").unwrap()
+}
+
+/// The code descriptions below the total : the looncodes in columns A and B, then the kengetallen in columns B
+/// and C.
+fn omschrijvingen(sheet: &Worksheet) -> (HashMap<String, String>, HashMap<String, String>) {
+    let (mut looncodes, mut kengetallen) = (HashMap::new(), HashMap::new());
+    for row in totaal_row(sheet) + 1..=sheet.get_highest_row() {
+        match (sheet.get_value((1, row)), sheet.get_value((2, row)), sheet.get_value((3, row))) {
+            (lc, omschrijving, _) if !lc.is_empty() => looncodes.insert(lc, omschrijving),
+            (_, wnk, omschrijving) if !wnk.is_empty() => kengetallen.insert(wnk, omschrijving),
+            _ => None,
+        };
+    }
+    (looncodes, kengetallen)
+}
+
+/// Writes the report with clear INSZ numbers, as in the verschil documents of the accountants.
+fn write_report(mut report: VerschilReport) -> NamedTempFile {
+    report.privacy = Privacy::new(PrivacyProfile::Clear, b"").unwrap();
+    let file = Builder::new().suffix(".xlsx").tempfile().unwrap();
+    report.write(file.path().to_str().unwrap()).unwrap();
+    file
+}
+
+#[test]
+fn test_golden_207527540_cipal() {
+    let vergelijking = compare_dmfa_cipal(
+        Path::new("tests/fixtures/207527540-dmfa.xlsx"),
+        Path::new("tests/fixtures/207527540-cipal.xlsx"),
+        &Mapping::standaard(),
+    ).unwrap();
+    assert!(vergelijking.niet_gemapt.is_empty());
+
+    let file = write_report(VerschilReport::new(&vergelijking.verschillen));
+    let actual = verschil_lines(file.path());
+
+    // The CIPAL fixture only covers 2023, the 2022 lines of the verschil document come from an older export.
+    let expected: HashMap<Key, Bedragen> = verschil_lines(Path::new("tests/fixtures/207527540-verschil.xlsx"))
+        .into_iter()
+        .filter(|(key, _)| key.0.starts_with("2023"))
+        .collect();
+    assert_eq!(expected.len(), 60);
+
+    assert_same_lines(&actual, &expected);
+}
+
+#[test]
+fn test_golden_207527540_layout() {
+    let vergelijking = compare_dmfa_cipal(
+        Path::new("tests/fixtures/207527540-dmfa.xlsx"),
+        Path::new("tests/fixtures/207527540-cipal.xlsx"),
+        &Mapping::standaard(),
+    ).unwrap();
+    let file = write_report(VerschilReport::new(&vergelijking.verschillen));
+
+    let actual = xlsx::read(file.path()).unwrap();
+    let expected = xlsx::read(Path::new("tests/fixtures/207527540-verschil.xlsx")).unwrap();
+    let (actual, expected) = (actual.get_sheet(&0).unwrap(), expected.get_sheet(&0).unwrap());
+
+    assert_eq!(actual.get_name(), expected.get_name());
+    assert_eq!(actual.get_value((1, 1)), expected.get_value((1, 1)));
+    for column in 1..=8 {
+        assert_eq!(actual.get_value((column, 4)), expected.get_value((column, 4)));
+    }
+
+    // The total is below the lines, the verschil document also holds the lines of 2022.
+    let expected_totaal: f64 = verschil_lines(Path::new("tests/fixtures/207527540-verschil.xlsx"))
+        .into_iter()
+        .filter(|(key, _)| key.0.starts_with("2023"))
+        .map(|(_, (_, _, verschil))| verschil)
+        .sum();
+    let (actual_row, expected_row) = (totaal_row(actual), totaal_row(expected));
+    let actual_totaal: f64 = actual.get_value((8, actual_row)).parse().unwrap();
+    assert!((actual_totaal - expected_totaal).abs() < TOLERANCE, "Totaal: {actual_totaal} != {expected_totaal}");
+
+    // The code descriptions start three rows below the total, the codes of both documents are described alike.
+    for (sheet, row) in [(actual, actual_row), (expected, expected_row)] {
+        assert!((row + 1..row + 3).all(|row| (1..=3).all(|column| sheet.get_value((column, row)).is_empty())));
+        assert!(!sheet.get_value((1, row + 3)).is_empty());
+    }
+    let (actual_looncodes, actual_kengetallen) = omschrijvingen(actual);
+    let (expected_looncodes, expected_kengetallen) = omschrijvingen(expected);
+    let mut shared = 0;
+    for (actual, expected) in [(actual_looncodes, expected_looncodes), (actual_kengetallen, expected_kengetallen)] {
+        for (code, omschrijving) in &actual {
+            if let Some(expected) = expected.get(code) {
+                assert_eq!(omschrijving, expected, "description of code {code}");
+                shared += 1;
+            }
+        }
+    }
+    // The kengetallen 21 and 481 are the codes of 2023 the verschil document describes
+    assert_eq!(shared, 2);
+}
+
+/// There is no BOSA export for 208322049, so it is rebuilt from the DMFA : every wage line of 2023/4 is
+/// copied as it was declared, except for the line the accountants found a difference for.
+fn synthetic_bosa(dmfa: &str) -> NamedTempFile {
+    let dmfa_reader = DmfaReader::new(dmfa).unwrap();
+    let data = dmfa_reader.data().unwrap();
+
+    let file = Builder::new().suffix(".xlsx").tempfile().unwrap();
+    let mut book = umya_spreadsheet::new_file();
+    let sheet = book.get_sheet_mut(&0).unwrap();
+    let header = ["KBO", "INSZ", "Periode", "WGC", "WNK", "Looncode", "Bedrag"];
+    for (column, value) in header.iter().enumerate() {
+        sheet.get_cell_mut((column as u32 + 1, 1)).set_value(*value);
+    }
+
+    let mut row = 2;
+    for entry in data.values().flat_map(|kwartalen| kwartalen.values()).flatten() {
+        if (entry.kwart.year, entry.kwart.quarter) != (2023, 4) {
+            continue;
+        }
+        let bedrag = if entry.insz.to_string() == "80080703041" && entry.wnk == 495 && entry.lc == 2 {
+            entry.brutto_loon + Money::from_cents(4409)
+        } else {
+            entry.brutto_loon
+        };
+        let values = [
+            dmfa_reader.kbo_nummer.to_string(),
+            entry.insz.to_string(),
+            "202310".to_string(),
+            entry.wgc.to_string(),
+            entry.wnk.to_string(),
+            entry.lc.to_string(),
+            bedrag.to_string().replace('.', ","),
+        ];
+        for (column, value) in values.iter().enumerate() {
+            sheet.get_cell_mut((column as u32 + 1, row)).set_value(value.as_str());
+        }
+        row += 1;
+    }
+
+    umya_spreadsheet::writer::xlsx::write(&book, file.path()).unwrap();
+    file
+}
+
+/// Not a golden test : the BOSA export is synthetic and the difference is injected, so this only checks that the
+/// BOSA path (read, compare, write the verschil document) runs end to end and finds the injected line.
+#[test]
+fn test_smoke_208322049_bosa() {
+    let dmfa = "tests/fixtures/208322049-dmfa.xlsx";
+    let bosa = synthetic_bosa(dmfa);
+    let verschillen = compare_dmfa_bosa(Path::new(dmfa), bosa.path()).unwrap();
+
+    let mut report = VerschilReport::new(&verschillen);
+    report.titel = "Bijlage 2: loonverschil ( berekening dd, 24,10,2024)".to_string();
+    let file = write_report(report);
+
+    let actual = verschil_lines(file.path());
+    let expected = verschil_lines(Path::new("tests/fixtures/208322049-verschil.xlsx"));
+    assert_eq!(expected.len(), 1);
+
+    assert_same_lines(&actual, &expected);
+}